@@ -1,60 +1,53 @@
 use std::error::Error;
 
-use regex::Regex;
-use ton_liteapi::tl::common::{Int256, BlockIdExt, AccountId};
+use ton_liteapi::boc::Cell;
+use ton_liteapi::tl::common::{BlockIdExt, AccountId};
+use ton_liteapi::tvm::{self, BigInt, TvmStackEntry};
 
 pub fn parse_block_id_ext(s: &str) -> std::result::Result<BlockIdExt, String> {
-    let re = Regex::new(r"\(([-]?\d+),([a-fA-F0-9]+),(\d+)\):([^:]+):(.+)").unwrap();
-
-    if let Some(capture) = re.captures(s) {
-        let workchain = capture[1]
-            .parse::<i32>()
-            .map_err(|e| format!("Can't parse workchain '{}': {:?}", &capture[1], e))?;
-        let shard = u64::from_str_radix(&capture[2], 16)
-            .map_err(|e| format!("Can't parse shard '{}': {:?}", &capture[2], e))?;
-        let seqno = capture[3]
-            .parse::<u32>()
-            .map_err(|e| format!("Can't parse seqno '{}': {:?}", &capture[3], e))?;
-        let root_hash = capture[4].parse::<Int256>()
-            .map_err(|e| format!("Can't parse root_hash '{}': {:?}", &capture[4], e))?;
-        let file_hash = capture[5].parse::<Int256>()
-            .map_err(|e| format!("Can't parse file_hash '{}': {:?}", &capture[5], e))?;
-        Ok(BlockIdExt {
-            workchain,
-            shard,
-            seqno,
-            root_hash,
-            file_hash,
-        })
-    } else {
-        Err("Wrong format, must be (workchain,shard_hex,seqno):root_hash:file_hash".into())
-    }
+    s.parse::<BlockIdExt>().map_err(|e| format!("Can't parse block id {}: {}", s, e))
 }
 
-fn parse_account_base64(s: &str) -> std::result::Result<AccountId, Box<dyn Error>> {
-    let res = base64::decode_config(s, base64::URL_SAFE)?;
-    if res.len() != 36 {
-        return Err(format!("Wrong length for base64 address, expected 36, got {}", res.len()).into())
-    }
-    let workchain = res[1] as i8;
-    let id = Int256((&res[2..34]).try_into()?);
-    Ok(AccountId {
-        workchain: workchain.into(),
-        id,
-    })
+pub fn parse_account_id(s: &str) -> std::result::Result<AccountId, String> {
+    s.parse::<AccountId>().map_err(|e| format!("Can't parse account {}: {}", s, e))
 }
 
-fn parse_account_raw(s: &str) -> std::result::Result<AccountId, Box<dyn Error>> {
-    let (workchain, account) = s.split_once(":").ok_or_else(|| format!("can't parse {}: wrong address format, must be <workchain>:<account>", s))?;
-    let workchain = workchain.parse::<i32>().map_err(|_e| format!("wrong workchain {}", workchain))?;
-    let id = account.parse::<Int256>().map_err(|_e| format!("wrong account id {}", account))?;
-    Ok(AccountId { workchain, id })
+pub fn parse_key(s: &str) -> std::result::Result<[u8; 32], Box<dyn Error + Send + Sync>> {
+    Ok(base64::decode(s).or_else(|_e| hex::decode(s)).map_err(|_e| "can't parse key")?.as_slice().try_into()?)
 }
 
-pub fn parse_account_id(s: &str) -> std::result::Result<AccountId, String> {
-    parse_account_base64(s).or_else(|e| parse_account_raw(s).map_err(|e2| format!("Can't parse account as base64 ({}) or as raw ({}))", e, e2)))
+/// Parses stack entry given as `null`, `nan`, decimal or `0x`-prefixed hex integer, or as
+/// `cell:<boc>`, `slice:<boc>` or `builder:<boc>` with bag of cells in hex or base64
+pub fn parse_stack_entry(s: &str) -> std::result::Result<TvmStackEntry, String> {
+    match s {
+        "null" => return Ok(TvmStackEntry::Null),
+        "nan" => return Ok(TvmStackEntry::NaN),
+        _ => {}
+    }
+    if let Some((kind, boc)) = s.split_once(':') {
+        let data = hex::decode(boc).or_else(|_e| base64::decode(boc)).map_err(|_e| format!("can't decode bag of cells {}", boc))?;
+        let cell = Cell::from_boc(&data).map_err(|e| format!("can't parse bag of cells {}: {}", boc, e))?;
+        return match kind {
+            "cell" => Ok(TvmStackEntry::Cell(cell)),
+            "slice" => Ok(TvmStackEntry::Slice(cell)),
+            "builder" => Ok(TvmStackEntry::Builder(cell)),
+            _ => Err(format!("unknown stack entry type {}, must be cell, slice or builder", kind)),
+        };
+    }
+    let value = match s.strip_prefix("0x") {
+        Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+        None => s.parse::<BigInt>().ok(),
+    };
+    value.map(TvmStackEntry::Int).ok_or_else(|| format!("can't parse stack entry {}", s))
 }
 
-pub fn parse_key(s: &str) -> std::result::Result<[u8; 32], Box<dyn Error + Send + Sync>> {
-    Ok(base64::decode(s).or_else(|_e| hex::decode(s)).map_err(|_e| "can't parse key")?.as_slice().try_into()?)
+/// Parses get-method id given as a number or as the name of the method
+pub fn parse_method_id(s: &str) -> std::result::Result<u64, String> {
+    if let Ok(id) = s.parse::<u64>() {
+        return Ok(id);
+    }
+    if s.is_empty() || s.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("can't parse method id {}", s));
+    }
+    Ok(tvm::method_id(s))
 }
\ No newline at end of file
@@ -3,10 +3,18 @@ mod arg_parsers;
 use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
 use rand::seq::SliceRandom as _;
+use ton_liteapi::address::Address;
+use ton_liteapi::block::{Account, AccountStorageState, Block, BlockchainConfig, ConfigParam, MsgAddress, ShardHashes, Transaction};
+use ton_liteapi::boc::Cell;
+use ton_liteapi::proof;
+use ton_liteapi::tvm::TvmStackEntry;
 use ton_liteapi::tl::common::{AccountId, BlockId, BlockIdExt, Int256, TransactionId3};
+use ton_liteapi::tl::response::ConfigInfo;
+use ton_liteapi::tl::request::{GetBlockHeader, GetBlockOutMsgQueueSize, GetBlockProof, GetConfigAll, GetConfigParams, GetDispatchQueueInfo, GetDispatchQueueMessages, ListBlockTransactions, LookupBlock, LookupBlockWithProof};
 use ton_liteapi::client::LiteClient;
+use ton_liteapi::pool::LitePool;
 use pretty_hex::PrettyHex;
-use ton_networkconfig::ConfigGlobal;
+use ton_networkconfig::{ConfigBlockId, ConfigGlobal};
 use std::error::Error;
 use std::fs::{read_to_string, File};
 use std::io::{stdin, Read};
@@ -15,7 +23,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::{Duration, UNIX_EPOCH};
 
-use crate::arg_parsers::{parse_account_id, parse_block_id_ext, parse_key};
+use crate::arg_parsers::{parse_account_id, parse_block_id_ext, parse_key, parse_method_id, parse_stack_entry};
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
@@ -34,6 +42,9 @@ struct Args {
     /// Liteserver public key (hex-encoded)
     #[clap(long, value_parser = parse_key, requires = "address")]
     public_key: Option<[u8; 32]>,
+    /// Request timeout in seconds
+    #[clap(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
     #[clap(subcommand)]
     command: Commands,
 }
@@ -50,7 +61,7 @@ enum Commands {
     GetTime,
     /// Shows server time, version and capabilities
     GetVersion,
-    /// Downloads and dumps specified block
+    /// Downloads and decodes specified block
     #[clap(arg_required_else_help = true)]
     GetBlock {
         #[clap(value_parser = parse_block_id_ext)]
@@ -89,14 +100,25 @@ enum Commands {
         #[clap(value_parser = parse_account_id)]
         account_id: AccountId,
     },
+    /// Download account state at specified block with pruned branches
+    GetAccountStatePrunned {
+        #[clap(value_parser = parse_block_id_ext)]
+        block_id_ext: BlockIdExt,
+        #[clap(value_parser = parse_account_id)]
+        account_id: AccountId,
+    },
     /// Run get-method for smart contract
     RunSmcMethod {
         #[clap(value_parser = parse_block_id_ext)]
         block_id_ext: BlockIdExt,
         #[clap(value_parser = parse_account_id)]
         account_id: AccountId,
+        /// Id of get-method or its name, e.g. seqno
+        #[clap(value_parser = parse_method_id)]
         method_id: u64,
-        params: Vec<u8>,
+        /// Stack entries from the bottom to the top: null, nan, integers, cell:<boc>, slice:<boc> or builder:<boc>
+        #[clap(value_parser = parse_stack_entry, allow_hyphen_values = true)]
+        params: Vec<TvmStackEntry>,
     },
     GetShardInfo {
         #[clap(value_parser = parse_block_id_ext)]
@@ -145,6 +167,29 @@ enum Commands {
         #[clap(long)]
         with_prev_blk_signatures: bool,
     },
+    /// Find block by seqno, lt or utime with proof of its inclusion into specified masterchain block
+    LookupBlockWithProof {
+        #[clap(value_parser = parse_block_id_ext)]
+        mc_block_id: BlockIdExt,
+        workchain: i32,
+        shard: u64,
+        #[clap(short, long, group = "lookup-variant")]
+        seqno: Option<u32>,
+        #[clap(short, long, group = "lookup-variant")]
+        lt: Option<u64>,
+        #[clap(short, long, group = "lookup-variant")]
+        utime: Option<u32>,
+        #[clap(long)]
+        with_state_update: bool,
+        #[clap(long)]
+        with_value_flow: bool,
+        #[clap(long)]
+        with_extra: bool,
+        #[clap(long)]
+        with_shard_hashes: bool,
+        #[clap(long)]
+        with_prev_blk_signatures: bool,
+    },
     /// List transactions for a specified block
     ListBlockTransactions {
         #[clap(value_parser = parse_block_id_ext)]
@@ -159,6 +204,20 @@ enum Commands {
         #[clap(requires = "after-account", long)]
         after_lt: Option<u64>,
     },
+    /// List serialized transactions for a specified block
+    ListBlockTransactionsExt {
+        #[clap(value_parser = parse_block_id_ext)]
+        block_id_ext: BlockIdExt,
+        count: u32,
+        #[clap(short, long)]
+        reverse_order: bool,
+        #[clap(short, long)]
+        want_proof: bool,
+        #[clap(requires = "after-lt", value_parser = parse_account_id, long)]
+        after_account: Option<AccountId>,
+        #[clap(requires = "after-account", long)]
+        after_lt: Option<u64>,
+    },
     /// Download masterchain proof
     GetBlockProof {
         #[clap(value_parser = parse_block_id_ext)]
@@ -170,6 +229,14 @@ enum Commands {
         #[clap(long)]
         base_block_from_request: bool,
     },
+    /// Prove masterchain block, or the last one, starting from a trusted block
+    VerifyBlockProof {
+        #[clap(value_parser = parse_block_id_ext)]
+        target_block: Option<BlockIdExt>,
+        /// Trusted block, by default the init block or the zerostate of network config
+        #[clap(long, value_parser = parse_block_id_ext)]
+        known_block: Option<BlockIdExt>,
+    },
     /// Download all config params
     GetConfigAll {
         #[clap(value_parser = parse_block_id_ext)]
@@ -236,6 +303,55 @@ enum Commands {
     GetLibraries {
         library_list: Vec<Int256>,
     },
+    /// Download specified libraries with proof against specified block
+    GetLibrariesWithProof {
+        #[clap(value_parser = parse_block_id_ext)]
+        block_id_ext: BlockIdExt,
+        library_list: Vec<Int256>,
+    },
+    /// Download proof of shard block inclusion into masterchain
+    GetShardBlockProof {
+        #[clap(value_parser = parse_block_id_ext)]
+        block_id_ext: BlockIdExt,
+    },
+    /// Show outbound message queue sizes of all shards or of a specified one
+    GetOutMsgQueueSizes {
+        #[clap(long, requires = "shard")]
+        workchain: Option<i32>,
+        #[clap(long, requires = "workchain")]
+        shard: Option<u64>,
+    },
+    /// Show outbound message queue size of a specified block
+    GetBlockOutMsgQueueSize {
+        #[clap(value_parser = parse_block_id_ext)]
+        block_id_ext: BlockIdExt,
+        #[clap(short, long)]
+        want_proof: bool,
+    },
+    /// List accounts having messages in dispatch queue of a specified block
+    GetDispatchQueueInfo {
+        #[clap(value_parser = parse_block_id_ext)]
+        block_id_ext: BlockIdExt,
+        max_accounts: u32,
+        #[clap(long)]
+        after_addr: Option<Int256>,
+        #[clap(short, long)]
+        want_proof: bool,
+    },
+    /// List messages from dispatch queue of a specified block
+    GetDispatchQueueMessages {
+        #[clap(value_parser = parse_block_id_ext)]
+        block_id_ext: BlockIdExt,
+        addr: Int256,
+        after_lt: u64,
+        max_messages: u32,
+        #[clap(short, long)]
+        want_proof: bool,
+        #[clap(long)]
+        one_account: bool,
+        #[clap(long)]
+        message_boc: bool,
+    },
 }
 
 #[tokio::main]
@@ -243,6 +359,7 @@ async fn main() -> Result<()> {
     env_logger::init();
     let args = Args::parse();
     
+    let mut trusted_block = None;
     let client = if let (Some(address), Some(public_key)) = (&args.address, &args.public_key) {
         LiteClient::connect(address, public_key).await?
     } else {
@@ -252,20 +369,27 @@ async fn main() -> Result<()> {
             download_config(args.testnet).await?
         };
         let config: ConfigGlobal = ConfigGlobal::from_str(&config_json)?;
-        let ls = config.liteservers.choose(&mut rand::thread_rng()).unwrap();
-        let public_key: [u8; 32] = ls.id.clone().into();
-        LiteClient::connect(ls.socket_addr(), public_key).await?
+        trusted_block = config.validator.as_ref().map(|validator| config_block_id(validator.init_block.as_ref().unwrap_or(&validator.zero_state)));
+        let mut liteservers: Vec<_> = config.liteservers.iter().map(|ls| {
+            let public_key: [u8; 32] = ls.id.clone().into();
+            (ls.socket_addr(), public_key)
+        }).collect();
+        liteservers.shuffle(&mut rand::thread_rng());
+        LiteClient::new(LitePool::connect(liteservers).await?)
     };
 
-    let mut client = client;
+    let client = match args.timeout {
+        Some(timeout) => client.default_timeout(Duration::from_secs(timeout)),
+        None => client,
+    };
 
-    if let Err(e) = execute_command(&mut client, &args.command).await {
+    if let Err(e) = execute_command(&client, &args.command, trusted_block.as_ref()).await {
         println!("[ERROR] {:?}", e);
     }
     Ok(())
 }
 
-async fn execute_command(client: &mut LiteClient, command: &Commands) -> Result<()> {
+async fn execute_command(client: &LiteClient, command: &Commands, trusted_block: Option<&BlockIdExt>) -> Result<()> {
     match command {
         Commands::GetMasterchainInfo => {
             let result = client.get_masterchain_info().await?;
@@ -288,21 +412,25 @@ async fn execute_command(client: &mut LiteClient, command: &Commands) -> Result<
         }
         Commands::GetBlock { block_id_ext } => {
             let result = client.get_block(block_id_ext.clone()).await?;
-            println!("BlockData: {:?}", result.hex_dump());
+            let block = Block::from_boc(&result)?;
+            if block.hash() != block_id_ext.root_hash.0 {
+                return Err("block root hash mismatch".into());
+            }
+            print_block(&block)?;
         }
         Commands::GetState { block_id_ext } => {
             let result = client.get_state(block_id_ext.clone()).await?;
             println!("{:#?}", result);
         }
         Commands::GetBlockHeader { block_id_ext, with_state_update, with_value_flow, with_extra, with_shard_hashes, with_prev_blk_signatures } => {
-            let result = client.get_block_header(
-                block_id_ext.clone(),
-                *with_state_update,
-                *with_value_flow,
-                *with_extra,
-                *with_shard_hashes,
-                *with_prev_blk_signatures,
-            ).await?;
+            let result = client.get_block_header(GetBlockHeader {
+                with_state_update: with_state_update.then_some(()),
+                with_value_flow: with_value_flow.then_some(()),
+                with_extra: with_extra.then_some(()),
+                with_shard_hashes: with_shard_hashes.then_some(()),
+                with_prev_blk_signatures: with_prev_blk_signatures.then_some(()),
+                ..GetBlockHeader::new(block_id_ext.clone())
+            }).await?;
             println!("Block Header: {:?}", result.hex_dump());
         }
         Commands::SendMessage { file } => {
@@ -317,11 +445,21 @@ async fn execute_command(client: &mut LiteClient, command: &Commands) -> Result<
         }
         Commands::GetAccountState { block_id_ext, account_id } => {
             let result = client.get_account_state(block_id_ext.clone(), account_id.clone()).await?;
+            println!("BlockIdExt: {}", result.id);
+            println!("Shard BlockIdExt: {}", result.shardblk);
+            print_account(Account::from_state(&result)?.as_ref());
+        }
+        Commands::GetAccountStatePrunned { block_id_ext, account_id } => {
+            let result = client.get_account_state_prunned(block_id_ext.clone(), account_id.clone()).await?;
             println!("{:#?}", result);
         }
         Commands::RunSmcMethod { block_id_ext, account_id, method_id, params } => {
-            let result = client.run_smc_method(0, block_id_ext.clone(), account_id.clone(), *method_id, params.clone()).await?;
-            println!("{:#?}", result);
+            let (state, result) = client.run_get_method_verified(block_id_ext.clone(), account_id.clone(), *method_id, params).await?;
+            println!("Shard BlockIdExt: {}", state.shard_block);
+            println!("Exit code: {}", result.exit_code);
+            for entry in &result.stack {
+                println!("{}", format_stack_entry(entry));
+            }
         }
         Commands::GetShardInfo { block_id_ext, workchain, shard, exact } => {
             let result = client.get_shard_info(block_id_ext.clone(), *workchain, *shard, *exact).await?;
@@ -329,29 +467,58 @@ async fn execute_command(client: &mut LiteClient, command: &Commands) -> Result<
         }
         Commands::GetAllShardsInfo { block_id_ext } => {
             let result = client.get_all_shards_info(block_id_ext.clone()).await?;
-            println!("{:#?}", result);
+            println!("{:#?}\n", result);
+            for tree in ShardHashes::parse(&Cell::from_boc(&result.data)?)?.workchains {
+                for (shard, descr) in &tree.shards {
+                    println!("Shard BlockIdExt: {}", descr.block_id(shard));
+                }
+            }
         }
         Commands::GetOneTransaction { block_id_ext, account_id, lt } => {
             let result = client.get_one_transaction(block_id_ext.clone(), account_id.clone(), *lt).await?;
-            println!("{:#?}", result);
+            println!("{:#?}\n", result);
+            if !result.transaction.is_empty() {
+                println!("{:#?}", Transaction::parse(&Cell::from_boc(&result.transaction)?)?);
+            }
         }
         Commands::GetTransactions { count, account_id, lt, hash } => {
             let result = client.get_transactions(*count, account_id.clone(), *lt, hash.clone()).await?;
-            println!("{:#?}", result);
+            for (id, transaction) in Transaction::from_list(&result)? {
+                println!("Transaction of BlockIdExt {}: {:#?}", id, transaction);
+            }
         }
         Commands::LookupBlock { workchain, shard, seqno, lt, utime, with_state_update, with_value_flow, with_extra, with_shard_hashes, with_prev_blk_signatures } => {
-            let result = client.lookup_block(
-                (),
-                BlockId { workchain: *workchain, shard: *shard, seqno: seqno.unwrap_or(0) },
-                seqno.map(|_| ()),
-                *lt,
-                *utime,
-                *with_state_update,
-                *with_value_flow,
-                *with_extra,
-                *with_shard_hashes,
-                *with_prev_blk_signatures,
-            ).await?;
+            let request = match (seqno, lt, utime) {
+                (Some(seqno), _, _) => LookupBlock::by_seqno(BlockId { workchain: *workchain, shard: *shard, seqno: *seqno }),
+                (_, Some(lt), _) => LookupBlock::by_lt(*workchain, *shard, *lt),
+                (_, _, Some(utime)) => LookupBlock::by_utime(*workchain, *shard, *utime),
+                _ => return Err("one of --seqno, --lt or --utime must be specified".into()),
+            };
+            let result = client.lookup_block(LookupBlock {
+                with_state_update: with_state_update.then_some(()),
+                with_value_flow: with_value_flow.then_some(()),
+                with_extra: with_extra.then_some(()),
+                with_shard_hashes: with_shard_hashes.then_some(()),
+                with_prev_blk_signatures: with_prev_blk_signatures.then_some(()),
+                ..request
+            }).await?;
+            println!("{:#?}", result);
+        }
+        Commands::LookupBlockWithProof { mc_block_id, workchain, shard, seqno, lt, utime, with_state_update, with_value_flow, with_extra, with_shard_hashes, with_prev_blk_signatures } => {
+            let request = match (seqno, lt, utime) {
+                (Some(seqno), _, _) => LookupBlockWithProof::by_seqno(BlockId { workchain: *workchain, shard: *shard, seqno: *seqno }, mc_block_id.clone()),
+                (_, Some(lt), _) => LookupBlockWithProof::by_lt(*workchain, *shard, *lt, mc_block_id.clone()),
+                (_, _, Some(utime)) => LookupBlockWithProof::by_utime(*workchain, *shard, *utime, mc_block_id.clone()),
+                _ => return Err("one of --seqno, --lt or --utime must be specified".into()),
+            };
+            let result = client.lookup_block_with_proof(LookupBlockWithProof {
+                with_state_update: with_state_update.then_some(()),
+                with_value_flow: with_value_flow.then_some(()),
+                with_extra: with_extra.then_some(()),
+                with_shard_hashes: with_shard_hashes.then_some(()),
+                with_prev_blk_signatures: with_prev_blk_signatures.then_some(()),
+                ..request
+            }).await?;
             println!("{:#?}", result);
         }
         Commands::ListBlockTransactions { block_id_ext, count, reverse_order, want_proof, after_account, after_lt } => {
@@ -359,58 +526,85 @@ async fn execute_command(client: &mut LiteClient, command: &Commands) -> Result<
                 account: account.id.clone(),
                 lt,
             }));
-            let result = client.list_block_transactions(
-                block_id_ext.clone(),
-                *count,
+            let result = client.list_block_transactions(ListBlockTransactions {
                 after,
-                *reverse_order,
-                *want_proof,
-            ).await?;
+                reverse_order: reverse_order.then_some(()),
+                want_proof: want_proof.then_some(()),
+                ..ListBlockTransactions::new(block_id_ext.clone(), *count)
+            }).await?;
+            println!("{:#?}", result);
+        }
+        Commands::ListBlockTransactionsExt { block_id_ext, count, reverse_order, want_proof, after_account, after_lt } => {
+            let after = after_account.as_ref().and_then(|account| after_lt.map(|lt| TransactionId3 {
+                account: account.id.clone(),
+                lt,
+            }));
+            let result = client.list_block_transactions_ext(ListBlockTransactions {
+                after,
+                reverse_order: reverse_order.then_some(()),
+                want_proof: want_proof.then_some(()),
+                ..ListBlockTransactions::new(block_id_ext.clone(), *count)
+            }).await?;
             println!("{:#?}", result);
         }
         Commands::GetBlockProof { known_block, target_block, allow_weak_target, base_block_from_request } => {
-            let result = client.get_block_proof(
-                known_block.clone(),
-                target_block.clone(),
-                *allow_weak_target,
-                *base_block_from_request,
-            ).await?;
+            let result = client.get_block_proof(GetBlockProof {
+                target_block: target_block.clone(),
+                allow_weak_target: allow_weak_target.then_some(()),
+                base_block_from_request: base_block_from_request.then_some(()),
+                ..GetBlockProof::new(known_block.clone())
+            }).await?;
             println!("{:#?}", result);
         }
+        Commands::VerifyBlockProof { target_block, known_block } => {
+            let trusted = known_block.as_ref().or(trusted_block).ok_or("--known-block is required without network config")?;
+            let result = client.get_block_proof_verified(trusted.clone(), target_block.clone()).await?;
+            println!("Proven masterchain BlockIdExt: {}", result);
+        }
         Commands::GetConfigAll { block_id_ext, with_state_root, with_libraries, with_state_extra_root, with_shard_hashes, with_validator_set, with_special_smc, with_accounts_root, with_prev_blocks, with_workchain_info, with_capabilities, extract_from_key_block } => {
-            let result = client.get_config_all(
-                block_id_ext.clone(),
-                *with_state_root,
-                *with_libraries,
-                *with_state_extra_root,
-                *with_shard_hashes,
-                *with_validator_set,
-                *with_special_smc,
-                *with_accounts_root,
-                *with_prev_blocks,
-                *with_workchain_info,
-                *with_capabilities,
-                *extract_from_key_block,
-            ).await?;
-            println!("{:#?}", result);
+            let result = client.get_config_all(GetConfigAll {
+                with_state_root: with_state_root.then_some(()),
+                with_libraries: with_libraries.then_some(()),
+                with_state_extra_root: with_state_extra_root.then_some(()),
+                with_shard_hashes: with_shard_hashes.then_some(()),
+                with_validator_set: with_validator_set.then_some(()),
+                with_special_smc: with_special_smc.then_some(()),
+                with_accounts_root: with_accounts_root.then_some(()),
+                with_prev_blocks: with_prev_blocks.then_some(()),
+                with_workchain_info: with_workchain_info.then_some(()),
+                with_capabilities: with_capabilities.then_some(()),
+                extract_from_key_block: extract_from_key_block.then_some(()),
+                ..GetConfigAll::new(block_id_ext.clone())
+            }).await?;
+            if let Some(config) = verified_config(block_id_ext, &result) {
+                println!("Config of BlockIdExt: {}", result.id);
+                for (index, param) in config.params()? {
+                    print_config_param(index, Some(&param));
+                }
+            }
         }
         Commands::GetConfigParams { block_id_ext, param_list, with_state_root, with_libraries, with_state_extra_root, with_shard_hashes, with_validator_set, with_special_smc, with_accounts_root, with_prev_blocks, with_workchain_info, with_capabilities, extract_from_key_block } => {
-            let result = client.get_config_params(
-                block_id_ext.clone(),
-                param_list.clone(),
-                *with_state_root,
-                *with_libraries,
-                *with_state_extra_root,
-                *with_shard_hashes,
-                *with_validator_set,
-                *with_special_smc,
-                *with_accounts_root,
-                *with_prev_blocks,
-                *with_workchain_info,
-                *with_capabilities,
-                *extract_from_key_block,
-            ).await?;
-            println!("{:#?}", result);
+            let result = client.get_config_params(GetConfigParams {
+                with_state_root: with_state_root.then_some(()),
+                with_libraries: with_libraries.then_some(()),
+                with_state_extra_root: with_state_extra_root.then_some(()),
+                with_shard_hashes: with_shard_hashes.then_some(()),
+                with_validator_set: with_validator_set.then_some(()),
+                with_special_smc: with_special_smc.then_some(()),
+                with_accounts_root: with_accounts_root.then_some(()),
+                with_prev_blocks: with_prev_blocks.then_some(()),
+                with_workchain_info: with_workchain_info.then_some(()),
+                with_capabilities: with_capabilities.then_some(()),
+                extract_from_key_block: extract_from_key_block.then_some(()),
+                ..GetConfigParams::new(block_id_ext.clone(), param_list.clone())
+            }).await?;
+            if let Some(config) = verified_config(block_id_ext, &result) {
+                println!("Config of BlockIdExt: {}", result.id);
+                for &index in param_list {
+                    let index = index as u32;
+                    print_config_param(index, config.get(index)?.as_ref());
+                }
+            }
         }
         Commands::GetValidatorStats { block_id_ext, limit, start_after, modified_after } => {
             let result = client.get_validator_stats(
@@ -425,10 +619,154 @@ async fn execute_command(client: &mut LiteClient, command: &Commands) -> Result<
             let result = client.get_libraries(library_list.clone()).await?;
             println!("{:#?}", result);
         }
+        Commands::GetLibrariesWithProof { block_id_ext, library_list } => {
+            let result = client.get_libraries_with_proof(block_id_ext.clone(), library_list.clone()).await?;
+            println!("{:#?}", result);
+        }
+        Commands::GetShardBlockProof { block_id_ext } => {
+            let result = client.get_shard_block_proof(block_id_ext.clone()).await?;
+            println!("{:#?}", result);
+        }
+        Commands::GetOutMsgQueueSizes { workchain, shard } => {
+            let shard_id = workchain.zip(*shard);
+            let result = client.get_out_msg_queue_sizes(shard_id).await?;
+            println!("{:#?}", result);
+        }
+        Commands::GetBlockOutMsgQueueSize { block_id_ext, want_proof } => {
+            let result = client.get_block_out_msg_queue_size(GetBlockOutMsgQueueSize {
+                want_proof: want_proof.then_some(()),
+                ..GetBlockOutMsgQueueSize::new(block_id_ext.clone())
+            }).await?;
+            println!("{:#?}", result);
+        }
+        Commands::GetDispatchQueueInfo { block_id_ext, max_accounts, after_addr, want_proof } => {
+            let result = client.get_dispatch_queue_info(GetDispatchQueueInfo {
+                after_addr: after_addr.clone(),
+                want_proof: want_proof.then_some(()),
+                ..GetDispatchQueueInfo::new(block_id_ext.clone(), *max_accounts)
+            }).await?;
+            println!("{:#?}", result);
+        }
+        Commands::GetDispatchQueueMessages { block_id_ext, addr, after_lt, max_messages, want_proof, one_account, message_boc } => {
+            let result = client.get_dispatch_queue_messages(GetDispatchQueueMessages {
+                want_proof: want_proof.then_some(()),
+                one_account: one_account.then_some(()),
+                message_boc: message_boc.then_some(()),
+                ..GetDispatchQueueMessages::new(block_id_ext.clone(), addr.clone(), *after_lt, *max_messages)
+            }).await?;
+            println!("{:#?}", result);
+        }
+    };
+    Ok(())
+}
+
+fn print_account(account: Option<&Account>) {
+    let Some(account) = account else {
+        println!("Account does not exist");
+        return;
     };
+    println!("Status: {:?}", account.status());
+    match &account.address {
+        MsgAddress::Std(address) => {
+            println!("Address: {}", address);
+            println!("Friendly address: {}", Address::new(address.clone()));
+        }
+        address => println!("Address: {:?}", address),
+    }
+    println!("Balance: {} nanoton", account.balance.grams);
+    for (currency, amount) in &account.balance.other {
+        println!("Extra currency {}: {}", currency, amount);
+    }
+    println!("Last transaction lt: {}", account.last_trans_lt);
+    println!("Storage used: {} cells, {} bits", account.storage_used.cells, account.storage_used.bits);
+    println!("Storage last paid: {}", account.last_paid);
+    if let Some(due_payment) = account.due_payment {
+        println!("Storage due payment: {} nanoton", due_payment);
+    }
+    match &account.state {
+        AccountStorageState::Active(state_init) => {
+            let hash = |hash: Option<Int256>| hash.map_or("none".to_string(), |hash| hash.to_string());
+            println!("Code hash: {}", hash(state_init.code_hash()));
+            println!("Data hash: {}", hash(state_init.data_hash()));
+        }
+        AccountStorageState::Frozen { state_hash } => println!("Frozen state hash: {}", state_hash),
+        AccountStorageState::Uninit => {}
+    }
+}
+
+fn print_block(block: &Block) -> Result<()> {
+    let info = block.info()?;
+    println!("Global id: {}", block.global_id);
+    println!("Shard: {}, seqno: {}, key block: {}", info.shard, info.seqno, info.key_block);
+    println!("Generated at: {}", info.gen_utime);
+    println!("Logical time: {}..{}", info.start_lt, info.end_lt);
+    for prev in info.prev_block_ids()? {
+        println!("Previous BlockIdExt: {}", prev);
+    }
+    if let Some(master) = info.master_block_id() {
+        println!("Masterchain BlockIdExt: {}", master);
+    }
+    let value_flow = block.value_flow()?;
+    println!("Fees collected: {} nanoton", value_flow.fees_collected.grams);
+    println!("Created: {} nanoton, minted: {} nanoton", value_flow.created.grams, value_flow.minted.grams);
+    let extra = block.extra()?;
+    println!("Inbound messages: {}, outbound messages: {}", extra.in_msgs()?.len(), extra.out_msgs()?.len());
+    for account_block in extra.account_blocks()? {
+        let lts: Vec<_> = account_block.transaction_cells()?.into_iter().map(|(lt, _)| lt.to_string()).collect();
+        println!("Account {}:{} transactions at lt {}", info.shard.workchain(), account_block.account, lts.join(", "));
+    }
+    if let Some(shard_hashes) = extra.shard_hashes()? {
+        for (shard, descr) in shard_hashes.workchains.iter().flat_map(|tree| &tree.shards) {
+            println!("Shard BlockIdExt: {}", descr.block_id(shard));
+        }
+    }
     Ok(())
 }
 
+fn format_stack_entry(entry: &TvmStackEntry) -> String {
+    match entry {
+        TvmStackEntry::Null => "null".to_string(),
+        TvmStackEntry::NaN => "nan".to_string(),
+        TvmStackEntry::Int(value) => value.to_string(),
+        TvmStackEntry::Cell(cell) => format!("cell:{}", hex::encode(cell.to_boc())),
+        TvmStackEntry::Slice(cell) => format!("slice:{}", hex::encode(cell.to_boc())),
+        TvmStackEntry::Builder(cell) => format!("builder:{}", hex::encode(cell.to_boc())),
+        TvmStackEntry::Tuple(items) => format!("[{}]", items.iter().map(format_stack_entry).collect::<Vec<_>>().join(", ")),
+        TvmStackEntry::Cont(cont) => format!("cont {}", hex::encode(cont.repr_hash())),
+    }
+}
+
+/// Checks the config proof, printing the answer as is if it can not be checked
+fn verified_config(block_id_ext: &BlockIdExt, result: &ConfigInfo) -> Option<BlockchainConfig> {
+    match proof::check_config_info(block_id_ext, result) {
+        Ok(config) => Some(BlockchainConfig::new(config)),
+        Err(e) => {
+            eprintln!("Config proof is not verified: {}", e);
+            println!("{:#?}", result);
+            None
+        }
+    }
+}
+
+fn print_config_param(index: u32, param: Option<&ConfigParam>) {
+    match param {
+        // params without typed decoding may be large trees of cells
+        Some(ConfigParam::Raw(cell)) => println!("ConfigParam {}: cell {}", index, hex::encode(cell.repr_hash())),
+        Some(param) => println!("ConfigParam {}: {:#?}", index, param),
+        None => println!("ConfigParam {}: not set", index),
+    }
+}
+
+fn config_block_id(id: &ConfigBlockId) -> BlockIdExt {
+    BlockIdExt {
+        workchain: id.workchain,
+        shard: id.shard as u64,
+        seqno: id.seqno,
+        root_hash: Int256(id.root_hash),
+        file_hash: Int256(id.file_hash),
+    }
+}
+
 async fn download_config(testnet: bool) -> Result<String> {
     let url = if testnet {
         "https://ton.org/testnet-global.config.json"
@@ -0,0 +1,141 @@
+//! Raw and user-friendly forms of account addresses
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::str::FromStr;
+
+use crc::{Crc, CRC_16_XMODEM};
+use thiserror::Error;
+
+use crate::tl::common::{AccountId, Int256};
+
+const CRC16: Crc<u16> = Crc::<u16>::new(&CRC_16_XMODEM);
+
+const FLAG_BOUNCEABLE: u8 = 0x11;
+const FLAG_NON_BOUNCEABLE: u8 = 0x51;
+const FLAG_TESTNET: u8 = 0x80;
+/// Length of user-friendly address in bytes and in base64
+const FRIENDLY_LEN: usize = 36;
+const FRIENDLY_BASE64_LEN: usize = 48;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("Invalid raw address: {0}")]
+    InvalidRaw(&'static str),
+    #[error("Invalid base64 of user-friendly address")]
+    InvalidBase64,
+    #[error("Invalid user-friendly address flags {0:#04x}")]
+    InvalidFlags(u8),
+    #[error("User-friendly address checksum mismatch")]
+    ChecksumMismatch,
+    #[error("Workchain {0} does not fit into user-friendly address")]
+    WorkchainOutOfRange(i32),
+}
+
+/// Address of account with the flags of its user-friendly form.
+///
+/// Parses both the raw form `workchain:hex` and the user-friendly base64 form with URL-safe or
+/// standard alphabet. Displayed in the user-friendly URL-safe form, or in the raw form if its
+/// workchain does not fit into 8 bits, see [`Address::to_raw`] and [`Address::to_friendly`] for the others.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Address {
+    pub account: AccountId,
+    /// Whether messages to the account should bounce if it is not initialized
+    pub bounceable: bool,
+    pub testnet: bool,
+}
+
+impl Address {
+    /// Bounceable mainnet address of the account
+    pub fn new(account: AccountId) -> Self {
+        Self { account, bounceable: true, testnet: false }
+    }
+
+    pub fn to_raw(&self) -> String {
+        self.account.to_string()
+    }
+
+    /// User-friendly form, which keeps the workchain in 8 bits
+    pub fn to_friendly(&self, url_safe: bool) -> Result<String, AddressError> {
+        let workchain = i8::try_from(self.account.workchain).map_err(|_| AddressError::WorkchainOutOfRange(self.account.workchain))?;
+        let mut data = Vec::with_capacity(FRIENDLY_LEN);
+        let flags = if self.bounceable { FLAG_BOUNCEABLE } else { FLAG_NON_BOUNCEABLE };
+        data.push(if self.testnet { flags | FLAG_TESTNET } else { flags });
+        data.push(workchain as u8);
+        data.extend_from_slice(&self.account.id.0);
+        data.extend_from_slice(&CRC16.checksum(&data).to_be_bytes());
+        Ok(base64::encode_config(data, if url_safe { base64::URL_SAFE } else { base64::STANDARD }))
+    }
+
+    fn from_raw(s: &str) -> Result<Self, AddressError> {
+        let (workchain, id) = s.split_once(':').ok_or(AddressError::InvalidRaw("expected workchain:account"))?;
+        let workchain = workchain.parse().map_err(|_| AddressError::InvalidRaw("invalid workchain"))?;
+        let id = Int256::from_hex(id).map_err(|_| AddressError::InvalidRaw("invalid account id"))?;
+        Ok(Self::new(AccountId { workchain, id }))
+    }
+
+    fn from_friendly(s: &str) -> Result<Self, AddressError> {
+        if s.len() != FRIENDLY_BASE64_LEN {
+            return Err(AddressError::InvalidBase64);
+        }
+        let config = if s.contains(['-', '_']) { base64::URL_SAFE } else { base64::STANDARD };
+        let data = base64::decode_config(s, config).map_err(|_| AddressError::InvalidBase64)?;
+        let (data, checksum) = data.split_at(FRIENDLY_LEN - 2);
+        if CRC16.checksum(data).to_be_bytes() != checksum {
+            return Err(AddressError::ChecksumMismatch);
+        }
+        let bounceable = match data[0] & !FLAG_TESTNET {
+            FLAG_BOUNCEABLE => true,
+            FLAG_NON_BOUNCEABLE => false,
+            _ => return Err(AddressError::InvalidFlags(data[0])),
+        };
+        Ok(Self {
+            account: AccountId { workchain: data[1] as i8 as i32, id: Int256(data[2..].try_into().unwrap()) },
+            bounceable,
+            testnet: data[0] & FLAG_TESTNET != 0,
+        })
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(':') {
+            Self::from_raw(s)
+        } else {
+            Self::from_friendly(s)
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_friendly(true) {
+            Ok(friendly) => f.write_str(&friendly),
+            Err(_) => f.write_str(&self.to_raw()),
+        }
+    }
+}
+
+impl From<AccountId> for Address {
+    fn from(account: AccountId) -> Self {
+        Self::new(account)
+    }
+}
+
+impl From<Address> for AccountId {
+    fn from(address: Address) -> Self {
+        address.account
+    }
+}
+
+/// Parses any form of [`Address`], its flags are dropped
+impl FromStr for AccountId {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.parse::<Address>()?.account)
+    }
+}
@@ -0,0 +1,65 @@
+use std::error::Error;
+
+use super::*;
+
+type TestResult = std::result::Result<(), Box<dyn Error>>;
+
+const RAW: &str = "0:83dfd552e63729b472fcbcc8c45ebcc6691702558b68ec7527e1ba403a0f31a8";
+const BOUNCEABLE: &str = "EQCD39VS5jcptHL8vMjEXrzGaRcCVYto7HUn4bpAOg8xqB2N";
+const NON_BOUNCEABLE: &str = "UQCD39VS5jcptHL8vMjEXrzGaRcCVYto7HUn4bpAOg8xqEBI";
+const TESTNET_BOUNCEABLE: &str = "kQCD39VS5jcptHL8vMjEXrzGaRcCVYto7HUn4bpAOg8xqKYH";
+const TESTNET_NON_BOUNCEABLE: &str = "0QCD39VS5jcptHL8vMjEXrzGaRcCVYto7HUn4bpAOg8xqPvC";
+
+#[test]
+fn test_address_forms() -> TestResult {
+    let raw: Address = RAW.parse()?;
+    assert_eq!((raw.bounceable, raw.testnet), (true, false));
+    assert_eq!(raw.to_raw(), RAW);
+    assert_eq!(raw.to_string(), BOUNCEABLE);
+
+    let bounceable: Address = BOUNCEABLE.parse()?;
+    assert_eq!(bounceable, raw);
+    let non_bounceable: Address = NON_BOUNCEABLE.parse()?;
+    assert_eq!((non_bounceable.bounceable, non_bounceable.testnet), (false, false));
+    assert_eq!(non_bounceable.account, raw.account);
+    assert_eq!(non_bounceable.to_string(), NON_BOUNCEABLE);
+
+    let testnet = Address { testnet: true, ..raw.clone() };
+    assert_eq!(testnet.to_string(), TESTNET_BOUNCEABLE);
+    assert_eq!(TESTNET_BOUNCEABLE.parse::<Address>()?, testnet);
+    let testnet = Address { bounceable: false, ..testnet };
+    assert_eq!(testnet.to_string(), TESTNET_NON_BOUNCEABLE);
+    assert_eq!(TESTNET_NON_BOUNCEABLE.parse::<Address>()?, testnet);
+
+    // standard base64 alphabet
+    let masterchain = Address::new(AccountId { workchain: -1, id: Int256([0xfb; 32]) });
+    let standard = masterchain.to_friendly(false)?;
+    assert!(standard.contains('+') || standard.contains('/'));
+    assert_eq!(standard.parse::<Address>()?, masterchain);
+    assert_eq!(masterchain.to_string().parse::<Address>()?, masterchain);
+
+    assert_eq!(BOUNCEABLE.parse::<AccountId>()?, raw.account);
+    assert_eq!(AccountId::from(raw.clone()).to_string(), RAW);
+
+    // workchains outside of 8 bits have only the raw form
+    let large = Address::new(AccountId { workchain: 1000, id: raw.account.id.clone() });
+    assert_eq!(large.to_friendly(true), Err(AddressError::WorkchainOutOfRange(1000)));
+    assert_eq!(large.to_string(), large.to_raw());
+    assert_eq!(large.to_string().parse::<Address>()?, large);
+    Ok(())
+}
+
+#[test]
+fn test_invalid_address() {
+    let typo = BOUNCEABLE.replace("EQCD", "EQCE");
+    assert_eq!(typo.parse::<Address>(), Err(AddressError::ChecksumMismatch));
+    assert_eq!(BOUNCEABLE[1..].parse::<Address>(), Err(AddressError::InvalidBase64));
+    assert_eq!(format!("{}!", &BOUNCEABLE[1..]).parse::<Address>(), Err(AddressError::InvalidBase64));
+    assert!(matches!("0:83df".parse::<Address>(), Err(AddressError::InvalidRaw(_))));
+    assert!(matches!(RAW.replace("0:", "x:").parse::<Address>(), Err(AddressError::InvalidRaw(_))));
+
+    let mut data = vec![0x22, 0];
+    data.extend_from_slice(&[0; 32]);
+    data.extend_from_slice(&CRC16.checksum(&data).to_be_bytes());
+    assert_eq!(base64::encode_config(data, base64::URL_SAFE).parse::<Address>(), Err(AddressError::InvalidFlags(0x22)));
+}
@@ -0,0 +1,117 @@
+use crate::boc::{BocError, Cell, CellSlice};
+use crate::tl::common::Int256;
+use crate::tl::response::AccountState;
+
+use super::{load_int256, load_maybe_reference, AccountStatus, CurrencyCollection, MsgAddress, Result, StorageUsed};
+
+/// Initial state of smart contract, `StateInit`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateInit {
+    pub fixed_prefix_length: Option<u8>,
+    /// Flags `tick` and `tock` of special accounts
+    pub special: Option<(bool, bool)>,
+    pub code: Option<Cell>,
+    pub data: Option<Cell>,
+    /// Root of `HashmapE 256 SimpleLib` with public libraries
+    pub library: Option<Cell>,
+}
+
+impl StateInit {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        let fixed_prefix_length = if slice.load_bit()? { Some(slice.load_uint(5)? as u8) } else { None };
+        let special = if slice.load_bit()? { Some((slice.load_bit()?, slice.load_bit()?)) } else { None };
+        Ok(Self {
+            fixed_prefix_length,
+            special,
+            code: load_maybe_reference(slice)?,
+            data: load_maybe_reference(slice)?,
+            library: load_maybe_reference(slice)?,
+        })
+    }
+
+    pub fn code_hash(&self) -> Option<Int256> {
+        self.code.as_ref().map(|code| Int256(code.repr_hash()))
+    }
+
+    pub fn data_hash(&self) -> Option<Int256> {
+        self.data.as_ref().map(|data| Int256(data.repr_hash()))
+    }
+}
+
+/// State of existing account
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountStorageState {
+    Uninit,
+    Active(StateInit),
+    /// Frozen account keeps only the hash of its `StateInit`
+    Frozen { state_hash: Int256 },
+}
+
+/// Existing account, `account_none` is decoded as `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Account {
+    pub address: MsgAddress,
+    pub storage_used: StorageUsed,
+    /// Hash of the dictionary of accounts with extra storage info, if any
+    pub storage_dict_hash: Option<Int256>,
+    /// Time of the last storage fee payment
+    pub last_paid: u32,
+    pub due_payment: Option<u128>,
+    pub last_trans_lt: u64,
+    pub balance: CurrencyCollection,
+    pub state: AccountStorageState,
+}
+
+impl Account {
+    pub fn parse(cell: &Cell) -> Result<Option<Self>> {
+        let mut slice = cell.parse()?;
+        if !slice.load_bit()? {
+            return Ok(None);
+        }
+        let address = MsgAddress::parse(&mut slice)?;
+        let storage_used = StorageUsed::parse(&mut slice)?;
+        // storage_extra_none$000 or storage_extra_info$001, in place of public_cells:(VarUInteger 7)
+        // which was always zero in older states
+        let storage_dict_hash = match slice.load_uint(3)? {
+            0b000 => None,
+            0b001 => Some(load_int256(&mut slice)?),
+            _ => return Err(BocError::InvalidCell("invalid storage extra info")),
+        };
+        let last_paid = slice.load_uint(32)? as u32;
+        let due_payment = if slice.load_bit()? { Some(slice.load_coins()?) } else { None };
+        let last_trans_lt = slice.load_uint(64)?;
+        let balance = CurrencyCollection::parse(&mut slice)?;
+        let state = if slice.load_bit()? {
+            AccountStorageState::Active(StateInit::parse(&mut slice)?)
+        } else if slice.load_bit()? {
+            AccountStorageState::Frozen { state_hash: load_int256(&mut slice)? }
+        } else {
+            AccountStorageState::Uninit
+        };
+        Ok(Some(Self { address, storage_used, storage_dict_hash, last_paid, due_payment, last_trans_lt, balance, state }))
+    }
+
+    /// Decodes the state of `liteServer.accountState`, `None` if the account does not exist
+    pub fn from_state(response: &AccountState) -> Result<Option<Self>> {
+        if response.state.is_empty() {
+            return Ok(None);
+        }
+        Self::parse(&Cell::from_boc(&response.state)?)
+    }
+
+    pub fn status(&self) -> AccountStatus {
+        match self.state {
+            AccountStorageState::Uninit => AccountStatus::Uninit,
+            AccountStorageState::Active(_) => AccountStatus::Active,
+            AccountStorageState::Frozen { .. } => AccountStatus::Frozen,
+        }
+    }
+
+    /// `StateInit` of active account
+    pub fn state_init(&self) -> Option<&StateInit> {
+        match &self.state {
+            AccountStorageState::Active(state_init) => Some(state_init),
+            _ => None,
+        }
+    }
+}
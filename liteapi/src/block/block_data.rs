@@ -0,0 +1,380 @@
+use crate::boc::{dict_entries, dict_entries_slice, BocError, Cell, CellSlice};
+use crate::tl::common::{BlockIdExt, Int256};
+
+use super::{load_int256, load_maybe_reference, CurrencyCollection, GlobalVersion, InMsg, OutMsg, Result, ShardHashes, ShardIdent, Transaction};
+
+const BLOCK_TAG: u64 = 0x11ef55aa;
+const BLOCK_INFO_TAG: u64 = 0x9bc7a987;
+const VALUE_FLOW_TAG: u64 = 0xb8e48dfb;
+const VALUE_FLOW_V2_TAG: u64 = 0x3ebf98b7;
+const BLOCK_EXTRA_TAG: u64 = 0x4a33f6fd;
+const MC_BLOCK_EXTRA_TAG: u64 = 0xcca5;
+const ACCOUNT_BLOCK_TAG: u64 = 0x5;
+
+/// Block, its parts are decoded on demand so that only the needed cells are visited
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub global_id: i32,
+    pub cell: Cell,
+    info: Cell,
+    value_flow: Cell,
+    extra: Cell,
+}
+
+impl Block {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        if slice.load_uint(32)? != BLOCK_TAG {
+            return Err(BocError::InvalidCell("invalid block tag"));
+        }
+        let global_id = slice.load_int(32)? as i32;
+        let info = slice.load_reference()?;
+        let value_flow = slice.load_reference()?;
+        // state_update
+        slice.load_reference()?;
+        let extra = slice.load_reference()?;
+        Ok(Self { global_id, cell: cell.clone(), info, value_flow, extra })
+    }
+
+    /// Decodes `BlockData.data`
+    pub fn from_boc(data: &[u8]) -> Result<Self> {
+        Self::parse(&Cell::from_boc(data)?)
+    }
+
+    /// Root hash of the block
+    pub fn hash(&self) -> [u8; 32] {
+        self.cell.repr_hash()
+    }
+
+    pub fn info(&self) -> Result<BlockInfo> {
+        BlockInfo::parse(&self.info)
+    }
+
+    pub fn value_flow(&self) -> Result<ValueFlow> {
+        ValueFlow::parse(&self.value_flow)
+    }
+
+    pub fn extra(&self) -> Result<BlockExtra> {
+        BlockExtra::parse(&self.extra)
+    }
+}
+
+/// Reference to another block, `ExtBlkRef`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtBlkRef {
+    pub end_lt: u64,
+    pub seqno: u32,
+    pub root_hash: Int256,
+    pub file_hash: Int256,
+}
+
+impl ExtBlkRef {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        Ok(Self {
+            end_lt: slice.load_uint(64)?,
+            seqno: slice.load_uint(32)? as u32,
+            root_hash: load_int256(slice)?,
+            file_hash: load_int256(slice)?,
+        })
+    }
+
+    /// Id of the referenced block in the shard
+    pub fn block_id(&self, shard: &ShardIdent) -> BlockIdExt {
+        BlockIdExt {
+            workchain: shard.workchain(),
+            shard: shard.shard(),
+            seqno: self.seqno,
+            root_hash: self.root_hash.clone(),
+            file_hash: self.file_hash.clone(),
+        }
+    }
+}
+
+/// Header of block, `BlockInfo`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockInfo {
+    pub version: u32,
+    pub after_merge: bool,
+    pub before_split: bool,
+    pub after_split: bool,
+    pub want_split: bool,
+    pub want_merge: bool,
+    pub key_block: bool,
+    pub seqno: u32,
+    pub vert_seqno: u32,
+    pub shard: ShardIdent,
+    pub gen_utime: u32,
+    pub start_lt: u64,
+    pub end_lt: u64,
+    pub gen_validator_list_hash_short: u32,
+    pub gen_catchain_seqno: u32,
+    pub min_ref_mc_seqno: u32,
+    pub prev_key_block_seqno: u32,
+    pub gen_software: Option<GlobalVersion>,
+    /// Last masterchain block known to shard block, `None` for masterchain blocks
+    pub master_ref: Option<ExtBlkRef>,
+    /// Previous block, or two previous blocks after merge
+    pub prev_refs: Vec<ExtBlkRef>,
+    /// Previous block in vertical chain, if vertical seqno was incremented
+    pub prev_vert_ref: Option<ExtBlkRef>,
+}
+
+impl BlockInfo {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        if slice.load_uint(32)? != BLOCK_INFO_TAG {
+            return Err(BocError::InvalidCell("invalid block info tag"));
+        }
+        let version = slice.load_uint(32)? as u32;
+        let not_master = slice.load_bit()?;
+        let after_merge = slice.load_bit()?;
+        let before_split = slice.load_bit()?;
+        let after_split = slice.load_bit()?;
+        let want_split = slice.load_bit()?;
+        let want_merge = slice.load_bit()?;
+        let key_block = slice.load_bit()?;
+        let vert_seqno_incr = slice.load_bit()?;
+        let flags = slice.load_uint(8)?;
+        let seqno = slice.load_uint(32)? as u32;
+        let vert_seqno = slice.load_uint(32)? as u32;
+        let shard = ShardIdent::parse(&mut slice)?;
+        let gen_utime = slice.load_uint(32)? as u32;
+        let start_lt = slice.load_uint(64)?;
+        let end_lt = slice.load_uint(64)?;
+        let gen_validator_list_hash_short = slice.load_uint(32)? as u32;
+        let gen_catchain_seqno = slice.load_uint(32)? as u32;
+        let min_ref_mc_seqno = slice.load_uint(32)? as u32;
+        let prev_key_block_seqno = slice.load_uint(32)? as u32;
+        let gen_software = if flags & 1 != 0 { Some(GlobalVersion::load(&mut slice)?) } else { None };
+        // master_info$_ master:ExtBlkRef
+        let master_ref = if not_master { Some(ExtBlkRef::parse(&mut slice.load_reference()?.parse()?)?) } else { None };
+        // prev_blk_info$_ prev:ExtBlkRef or prev_blks_info$_ prev1:^ExtBlkRef prev2:^ExtBlkRef
+        let mut prev = slice.load_reference()?.parse()?;
+        let prev_refs = if after_merge {
+            vec![ExtBlkRef::parse(&mut prev.load_reference()?.parse()?)?, ExtBlkRef::parse(&mut prev.load_reference()?.parse()?)?]
+        } else {
+            vec![ExtBlkRef::parse(&mut prev)?]
+        };
+        let prev_vert_ref = if vert_seqno_incr { Some(ExtBlkRef::parse(&mut slice.load_reference()?.parse()?)?) } else { None };
+        Ok(Self {
+            version,
+            after_merge,
+            before_split,
+            after_split,
+            want_split,
+            want_merge,
+            key_block,
+            seqno,
+            vert_seqno,
+            shard,
+            gen_utime,
+            start_lt,
+            end_lt,
+            gen_validator_list_hash_short,
+            gen_catchain_seqno,
+            min_ref_mc_seqno,
+            prev_key_block_seqno,
+            gen_software,
+            master_ref,
+            prev_refs,
+            prev_vert_ref,
+        })
+    }
+
+    pub fn is_masterchain(&self) -> bool {
+        self.master_ref.is_none()
+    }
+
+    /// Ids of previous blocks, their shards follow from split and merge flags
+    pub fn prev_block_ids(&self) -> Result<Vec<BlockIdExt>> {
+        if self.after_merge {
+            let (left, right) = self.shard.split().ok_or(BocError::InvalidCell("merged block of the deepest shard"))?;
+            return Ok(vec![self.prev_refs[0].block_id(&left), self.prev_refs[1].block_id(&right)]);
+        }
+        let shard = if self.after_split { self.shard.parent().ok_or(BocError::InvalidCell("split block of the full shard"))? } else { self.shard };
+        Ok(vec![self.prev_refs[0].block_id(&shard)])
+    }
+
+    /// Id of the last masterchain block known to shard block
+    pub fn master_block_id(&self) -> Option<BlockIdExt> {
+        self.master_ref.as_ref().map(|master| master.block_id(&ShardIdent::full(-1)))
+    }
+}
+
+/// Movement of currencies in block, `ValueFlow`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueFlow {
+    pub from_prev_blk: CurrencyCollection,
+    pub to_next_blk: CurrencyCollection,
+    pub imported: CurrencyCollection,
+    pub exported: CurrencyCollection,
+    pub fees_collected: CurrencyCollection,
+    /// Zero in `value_flow#b8e48dfb` that does not have it
+    pub burned: CurrencyCollection,
+    pub fees_imported: CurrencyCollection,
+    pub recovered: CurrencyCollection,
+    pub created: CurrencyCollection,
+    pub minted: CurrencyCollection,
+}
+
+impl ValueFlow {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        let v2 = match slice.load_uint(32)? {
+            VALUE_FLOW_TAG => false,
+            VALUE_FLOW_V2_TAG => true,
+            _ => return Err(BocError::InvalidCell("invalid value flow tag")),
+        };
+        let mut first = slice.load_reference()?.parse()?;
+        let fees_collected = CurrencyCollection::parse(&mut slice)?;
+        let burned = if v2 { CurrencyCollection::parse(&mut slice)? } else { CurrencyCollection::default() };
+        let mut second = slice.load_reference()?.parse()?;
+        Ok(Self {
+            from_prev_blk: CurrencyCollection::parse(&mut first)?,
+            to_next_blk: CurrencyCollection::parse(&mut first)?,
+            imported: CurrencyCollection::parse(&mut first)?,
+            exported: CurrencyCollection::parse(&mut first)?,
+            fees_collected,
+            burned,
+            fees_imported: CurrencyCollection::parse(&mut second)?,
+            recovered: CurrencyCollection::parse(&mut second)?,
+            created: CurrencyCollection::parse(&mut second)?,
+            minted: CurrencyCollection::parse(&mut second)?,
+        })
+    }
+}
+
+/// Contents of block, `BlockExtra`, its dictionaries are decoded on demand
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockExtra {
+    pub rand_seed: Int256,
+    /// Public key of the validator that created the block
+    pub created_by: Int256,
+    in_msg_descr: Cell,
+    out_msg_descr: Cell,
+    account_blocks: Cell,
+    custom: Option<Cell>,
+}
+
+impl BlockExtra {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        if slice.load_uint(32)? != BLOCK_EXTRA_TAG {
+            return Err(BocError::InvalidCell("invalid block extra tag"));
+        }
+        let in_msg_descr = slice.load_reference()?;
+        let out_msg_descr = slice.load_reference()?;
+        let account_blocks = slice.load_reference()?;
+        Ok(Self {
+            rand_seed: load_int256(&mut slice)?,
+            created_by: load_int256(&mut slice)?,
+            in_msg_descr,
+            out_msg_descr,
+            account_blocks,
+            custom: load_maybe_reference(&mut slice)?,
+        })
+    }
+
+    /// Inbound messages in the order of their hashes, `InMsgDescr`
+    pub fn in_msgs(&self) -> Result<Vec<InMsg>> {
+        // import_fees$_ fees_collected:Grams value_imported:CurrencyCollection
+        aug_dict_entries(&self.in_msg_descr, 256, |slice| {
+            slice.load_coins()?;
+            CurrencyCollection::parse(slice).map(drop)
+        })?
+        .into_iter()
+        .map(|(key, mut value)| InMsg::parse(key_int256(key), &mut value))
+        .collect()
+    }
+
+    /// Outbound messages in the order of their hashes, `OutMsgDescr`
+    pub fn out_msgs(&self) -> Result<Vec<OutMsg>> {
+        aug_dict_entries(&self.out_msg_descr, 256, skip_currency_collection)?
+            .into_iter()
+            .map(|(key, mut value)| OutMsg::parse(key_int256(key), &mut value))
+            .collect()
+    }
+
+    /// Accounts with transactions in the block in the order of their addresses, `ShardAccountBlocks`
+    pub fn account_blocks(&self) -> Result<Vec<AccountBlock>> {
+        aug_dict_entries(&self.account_blocks, 256, skip_currency_collection)?
+            .into_iter()
+            .map(|(_, mut value)| AccountBlock::parse(&mut value))
+            .collect()
+    }
+
+    /// Raw `McBlockExtra` of masterchain block
+    pub fn mc_extra(&self) -> Option<&Cell> {
+        self.custom.as_ref()
+    }
+
+    /// Top shard blocks registered in masterchain block, `None` for shard blocks
+    pub fn shard_hashes(&self) -> Result<Option<ShardHashes>> {
+        let Some(custom) = &self.custom else {
+            return Ok(None);
+        };
+        let mut slice = custom.parse()?;
+        if slice.load_uint(16)? != MC_BLOCK_EXTRA_TAG {
+            return Err(BocError::InvalidCell("invalid masterchain block extra tag"));
+        }
+        // key_block
+        slice.skip_bits(1)?;
+        Ok(Some(ShardHashes::load(&mut slice)?))
+    }
+}
+
+/// Transactions of account in block, `AccountBlock`, transactions are decoded on demand
+#[derive(Debug, Clone)]
+pub struct AccountBlock {
+    /// Account address within the workchain of block
+    pub account: Int256,
+    transactions: CellSlice,
+}
+
+impl AccountBlock {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        if slice.load_uint(4)? != ACCOUNT_BLOCK_TAG {
+            return Err(BocError::InvalidCell("invalid account block tag"));
+        }
+        let account = load_int256(slice)?;
+        Ok(Self { account, transactions: slice.clone() })
+    }
+
+    /// Cells of transactions by their logical times, in increasing order
+    pub fn transaction_cells(&self) -> Result<Vec<(u64, Cell)>> {
+        // transactions:(HashmapAug 64 ^Transaction CurrencyCollection) is stored inline
+        dict_entries_slice(self.transactions.clone(), 64)?
+            .into_iter()
+            .map(|(key, mut value)| {
+                skip_currency_collection(&mut value)?;
+                Ok((u64::from_be_bytes(key.try_into().unwrap()), value.load_reference()?))
+            })
+            .collect()
+    }
+
+    pub fn transactions(&self) -> Result<Vec<Transaction>> {
+        self.transaction_cells()?.iter().map(|(_, cell)| Transaction::parse(cell)).collect()
+    }
+}
+
+/// Entries of `HashmapAugE` with values following the extra of `skip_extra`
+fn aug_dict_entries(cell: &Cell, key_bits: usize, skip_extra: fn(&mut CellSlice) -> Result<()>) -> Result<Vec<(Vec<u8>, CellSlice)>> {
+    let Some(root) = load_maybe_reference(&mut cell.parse()?)? else {
+        return Ok(Vec::new());
+    };
+    dict_entries(&root, key_bits)?
+        .into_iter()
+        .map(|(key, mut value)| {
+            skip_extra(&mut value)?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+fn skip_currency_collection(slice: &mut CellSlice) -> Result<()> {
+    CurrencyCollection::parse(slice).map(drop)
+}
+
+fn key_int256(key: Vec<u8>) -> Int256 {
+    Int256(key.try_into().unwrap())
+}
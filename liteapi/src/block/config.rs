@@ -0,0 +1,522 @@
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::boc::{dict_entries, dict_get, dict_get_slice, BocError, Cell, CellSlice};
+use crate::tl::common::Int256;
+
+use super::{load_int256, load_maybe_reference, Result};
+
+const GLOBAL_VERSION_TAG: u64 = 0xc4;
+const WORKCHAIN_TAG: u64 = 0xa6;
+const WORKCHAIN_V2_TAG: u64 = 0xa7;
+const STORAGE_PRICES_TAG: u64 = 0xcc;
+const GAS_PRICES_TAG: u64 = 0xdd;
+const GAS_PRICES_EXT_TAG: u64 = 0xde;
+const GAS_FLAT_PFX_TAG: u64 = 0xd1;
+const MSG_FORWARD_PRICES_TAG: u64 = 0xea;
+const CATCHAIN_CONFIG_TAG: u64 = 0xc1;
+const CATCHAIN_CONFIG_NEW_TAG: u64 = 0xc2;
+const VALIDATORS_TAG: u64 = 0x11;
+const VALIDATORS_EXT_TAG: u64 = 0x12;
+const VALIDATOR_TAG: u64 = 0x53;
+const VALIDATOR_ADDR_TAG: u64 = 0x73;
+const ED25519_PUBKEY_TAG: u64 = 0x8e81278a;
+
+/// TL id of `pub.ed25519 key:int256 = PublicKey`
+const PUB_ED25519_ID: u32 = 0x4813b4c6;
+
+/// Blockchain config, the dictionary `Hashmap 32 ^Cell` of config params.
+///
+/// The dictionary may come from a Merkle proof keeping only some of the params, so params are
+/// decoded when they are requested.
+#[derive(Debug, Clone)]
+pub struct BlockchainConfig {
+    root: Cell,
+}
+
+impl BlockchainConfig {
+    pub fn new(root: Cell) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Cell {
+        &self.root
+    }
+
+    /// Raw cell of config param, `None` if the param is not set
+    pub fn param(&self, index: u32) -> Result<Option<Cell>> {
+        match dict_get(&self.root, &index.to_be_bytes(), 32)? {
+            Some(mut value) => Ok(Some(value.load_reference()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Decoded config param, `None` if the param is not set
+    pub fn get(&self, index: u32) -> Result<Option<ConfigParam>> {
+        self.param(index)?.map(|cell| ConfigParam::parse(index, &cell)).transpose()
+    }
+
+    /// All config params in the order of their indexes, the dictionary must not be pruned
+    pub fn params(&self) -> Result<Vec<(u32, ConfigParam)>> {
+        dict_entries(&self.root, 32)?
+            .into_iter()
+            .map(|(key, mut value)| {
+                let index = u32::from_be_bytes(key.try_into().unwrap());
+                Ok((index, ConfigParam::parse(index, &value.load_reference()?)?))
+            })
+            .collect()
+    }
+
+    /// Address of config smart contract in masterchain, param 0
+    pub fn config_address(&self) -> Result<Option<Int256>> {
+        self.param(0)?.map(|cell| load_int256(&mut cell.parse()?)).transpose()
+    }
+
+    /// Address of elector smart contract in masterchain, param 1
+    pub fn elector_address(&self) -> Result<Option<Int256>> {
+        self.param(1)?.map(|cell| load_int256(&mut cell.parse()?)).transpose()
+    }
+
+    /// Param 8
+    pub fn global_version(&self) -> Result<Option<GlobalVersion>> {
+        self.param(8)?.map(|cell| GlobalVersion::parse(&cell)).transpose()
+    }
+
+    /// Descriptions of workchains by their ids, param 12
+    pub fn workchains(&self) -> Result<Option<Vec<(i32, WorkchainDescr)>>> {
+        self.param(12)?.map(|cell| WorkchainDescr::parse_all(&cell)).transpose()
+    }
+
+    /// Storage prices by the time they take effect, param 18
+    pub fn storage_prices(&self) -> Result<Option<Vec<StoragePrices>>> {
+        self.param(18)?.map(|cell| StoragePrices::parse_all(&cell)).transpose()
+    }
+
+    /// Gas prices of masterchain (param 20) or basechain (param 21)
+    pub fn gas_prices(&self, masterchain: bool) -> Result<Option<GasPrices>> {
+        self.param(if masterchain { 20 } else { 21 })?.map(|cell| GasPrices::parse(&cell)).transpose()
+    }
+
+    /// Message forwarding prices of masterchain (param 24) or basechain (param 25)
+    pub fn msg_forward_prices(&self, masterchain: bool) -> Result<Option<MsgForwardPrices>> {
+        self.param(if masterchain { 24 } else { 25 })?.map(|cell| MsgForwardPrices::parse(&cell)).transpose()
+    }
+
+    /// Param 28
+    pub fn catchain_config(&self) -> Result<Option<CatchainConfig>> {
+        self.param(28)?.map(|cell| CatchainConfig::parse(&cell)).transpose()
+    }
+
+    /// Validator set from param 32 (previous), 34 (current) or 36 (next)
+    pub fn validator_set(&self, index: u32) -> Result<Option<ValidatorSet>> {
+        self.param(index)?.map(|cell| ValidatorSet::parse(&cell)).transpose()
+    }
+}
+
+/// Config param decoded by its index
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigParam {
+    /// Param 0
+    ConfigAddress(Int256),
+    /// Param 1
+    ElectorAddress(Int256),
+    /// Param 2
+    MinterAddress(Int256),
+    /// Param 3
+    FeeCollectorAddress(Int256),
+    /// Param 4
+    DnsRootAddress(Int256),
+    /// Param 8
+    GlobalVersion(GlobalVersion),
+    /// Param 12
+    Workchains(Vec<(i32, WorkchainDescr)>),
+    /// Param 18
+    StoragePrices(Vec<StoragePrices>),
+    /// Params 20 and 21
+    GasPrices(GasPrices),
+    /// Params 24 and 25
+    MsgForwardPrices(MsgForwardPrices),
+    /// Param 28
+    CatchainConfig(CatchainConfig),
+    /// Params 32 to 37
+    ValidatorSet(ValidatorSet),
+    /// Params without typed decoding
+    Raw(Cell),
+}
+
+impl ConfigParam {
+    pub fn parse(index: u32, cell: &Cell) -> Result<Self> {
+        Ok(match index {
+            0..=4 => {
+                let address = load_int256(&mut cell.parse()?)?;
+                match index {
+                    0 => Self::ConfigAddress(address),
+                    1 => Self::ElectorAddress(address),
+                    2 => Self::MinterAddress(address),
+                    3 => Self::FeeCollectorAddress(address),
+                    _ => Self::DnsRootAddress(address),
+                }
+            }
+            8 => Self::GlobalVersion(GlobalVersion::parse(cell)?),
+            12 => Self::Workchains(WorkchainDescr::parse_all(cell)?),
+            18 => Self::StoragePrices(StoragePrices::parse_all(cell)?),
+            20 | 21 => Self::GasPrices(GasPrices::parse(cell)?),
+            24 | 25 => Self::MsgForwardPrices(MsgForwardPrices::parse(cell)?),
+            28 => Self::CatchainConfig(CatchainConfig::parse(cell)?),
+            32..=37 => Self::ValidatorSet(ValidatorSet::parse(cell)?),
+            _ => Self::Raw(cell.clone()),
+        })
+    }
+}
+
+/// Version of the network and enabled capabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalVersion {
+    pub version: u32,
+    pub capabilities: u64,
+}
+
+impl GlobalVersion {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        Self::load(&mut cell.parse()?)
+    }
+
+    /// Decodes `GlobalVersion` stored inline, as in `BlockInfo`
+    pub fn load(slice: &mut CellSlice) -> Result<Self> {
+        if slice.load_uint(8)? != GLOBAL_VERSION_TAG {
+            return Err(BocError::InvalidCell("invalid global version tag"));
+        }
+        let version = slice.load_uint(32)? as u32;
+        let capabilities = slice.load_uint(64)?;
+        Ok(Self { version, capabilities })
+    }
+}
+
+/// Description of workchain, its format is not decoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkchainDescr {
+    pub enabled_since: u32,
+    pub actual_min_split: u8,
+    pub min_split: u8,
+    pub max_split: u8,
+    pub basic: bool,
+    pub active: bool,
+    pub accept_msgs: bool,
+    pub zerostate_root_hash: Int256,
+    pub zerostate_file_hash: Int256,
+    pub version: u32,
+}
+
+impl WorkchainDescr {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        let tag = slice.load_uint(8)?;
+        if tag != WORKCHAIN_TAG && tag != WORKCHAIN_V2_TAG {
+            return Err(BocError::InvalidCell("invalid workchain description tag"));
+        }
+        let enabled_since = slice.load_uint(32)? as u32;
+        let actual_min_split = slice.load_uint(8)? as u8;
+        let min_split = slice.load_uint(8)? as u8;
+        let max_split = slice.load_uint(8)? as u8;
+        let basic = slice.load_bit()?;
+        let active = slice.load_bit()?;
+        let accept_msgs = slice.load_bit()?;
+        // flags
+        slice.skip_bits(13)?;
+        let zerostate_root_hash = load_int256(slice)?;
+        let zerostate_file_hash = load_int256(slice)?;
+        let version = slice.load_uint(32)? as u32;
+        Ok(Self { enabled_since, actual_min_split, min_split, max_split, basic, active, accept_msgs, zerostate_root_hash, zerostate_file_hash, version })
+    }
+
+    /// Decodes `workchains:(HashmapE 32 WorkchainDescr)` of config param 12
+    fn parse_all(cell: &Cell) -> Result<Vec<(i32, Self)>> {
+        let Some(root) = load_maybe_reference(&mut cell.parse()?)? else {
+            return Ok(Vec::new());
+        };
+        dict_entries(&root, 32)?
+            .into_iter()
+            .map(|(key, mut value)| Ok((i32::from_be_bytes(key.try_into().unwrap()), Self::parse(&mut value)?)))
+            .collect()
+    }
+}
+
+/// Storage prices per second, in nanotons shifted left by 16 bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoragePrices {
+    /// Time the prices take effect
+    pub utime_since: u32,
+    pub bit_price_ps: u64,
+    pub cell_price_ps: u64,
+    pub mc_bit_price_ps: u64,
+    pub mc_cell_price_ps: u64,
+}
+
+impl StoragePrices {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        if slice.load_uint(8)? != STORAGE_PRICES_TAG {
+            return Err(BocError::InvalidCell("invalid storage prices tag"));
+        }
+        Ok(Self {
+            utime_since: slice.load_uint(32)? as u32,
+            bit_price_ps: slice.load_uint(64)?,
+            cell_price_ps: slice.load_uint(64)?,
+            mc_bit_price_ps: slice.load_uint(64)?,
+            mc_cell_price_ps: slice.load_uint(64)?,
+        })
+    }
+
+    /// Decodes `Hashmap 32 StoragePrices` of config param 18
+    fn parse_all(cell: &Cell) -> Result<Vec<Self>> {
+        dict_entries(cell, 32)?.into_iter().map(|(_, mut value)| Self::parse(&mut value)).collect()
+    }
+}
+
+/// Gas limits and prices, prices are in nanotons shifted left by 16 bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasPrices {
+    /// Gas consumed for flat price, zero if not set
+    pub flat_gas_limit: u64,
+    pub flat_gas_price: u64,
+    pub gas_price: u64,
+    pub gas_limit: u64,
+    /// Gas limit of special accounts, same as `gas_limit` if not set
+    pub special_gas_limit: u64,
+    pub gas_credit: u64,
+    pub block_gas_limit: u64,
+    pub freeze_due_limit: u64,
+    pub delete_due_limit: u64,
+}
+
+impl GasPrices {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        let mut tag = slice.load_uint(8)?;
+        let (mut flat_gas_limit, mut flat_gas_price) = (0, 0);
+        if tag == GAS_FLAT_PFX_TAG {
+            flat_gas_limit = slice.load_uint(64)?;
+            flat_gas_price = slice.load_uint(64)?;
+            tag = slice.load_uint(8)?;
+        }
+        if tag != GAS_PRICES_TAG && tag != GAS_PRICES_EXT_TAG {
+            return Err(BocError::InvalidCell("invalid gas prices tag"));
+        }
+        let gas_price = slice.load_uint(64)?;
+        let gas_limit = slice.load_uint(64)?;
+        let special_gas_limit = if tag == GAS_PRICES_EXT_TAG { slice.load_uint(64)? } else { gas_limit };
+        Ok(Self {
+            flat_gas_limit,
+            flat_gas_price,
+            gas_price,
+            gas_limit,
+            special_gas_limit,
+            gas_credit: slice.load_uint(64)?,
+            block_gas_limit: slice.load_uint(64)?,
+            freeze_due_limit: slice.load_uint(64)?,
+            delete_due_limit: slice.load_uint(64)?,
+        })
+    }
+}
+
+/// Message forwarding prices, prices are in nanotons shifted left by 16 bits and fractions are
+/// of 65536
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MsgForwardPrices {
+    pub lump_price: u64,
+    pub bit_price: u64,
+    pub cell_price: u64,
+    pub ihr_price_factor: u32,
+    pub first_frac: u16,
+    pub next_frac: u16,
+}
+
+impl MsgForwardPrices {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        if slice.load_uint(8)? != MSG_FORWARD_PRICES_TAG {
+            return Err(BocError::InvalidCell("invalid message forward prices tag"));
+        }
+        Ok(Self {
+            lump_price: slice.load_uint(64)?,
+            bit_price: slice.load_uint(64)?,
+            cell_price: slice.load_uint(64)?,
+            ihr_price_factor: slice.load_uint(32)? as u32,
+            first_frac: slice.load_uint(16)? as u16,
+            next_frac: slice.load_uint(16)? as u16,
+        })
+    }
+}
+
+/// Lifetimes of validator groups and the way their validators are selected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatchainConfig {
+    /// Whether masterchain validators are shuffled for each catchain, see [`ValidatorSet::masterchain_validators`]
+    pub shuffle_mc_validators: bool,
+    pub mc_catchain_lifetime: u32,
+    pub shard_catchain_lifetime: u32,
+    pub shard_validators_lifetime: u32,
+    pub shard_validators_num: u32,
+}
+
+impl CatchainConfig {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        // catchain_config_new#c2 adds flags:(## 7) { flags = 0 } shuffle_mc_validators:Bool
+        let shuffle_mc_validators = match slice.load_uint(8)? {
+            CATCHAIN_CONFIG_TAG => false,
+            CATCHAIN_CONFIG_NEW_TAG => {
+                if slice.load_uint(7)? != 0 {
+                    return Err(BocError::InvalidCell("invalid catchain config flags"));
+                }
+                slice.load_bit()?
+            }
+            _ => return Err(BocError::InvalidCell("invalid catchain config tag")),
+        };
+        Ok(Self {
+            shuffle_mc_validators,
+            mc_catchain_lifetime: slice.load_uint(32)? as u32,
+            shard_catchain_lifetime: slice.load_uint(32)? as u32,
+            shard_validators_lifetime: slice.load_uint(32)? as u32,
+            shard_validators_num: slice.load_uint(32)? as u32,
+        })
+    }
+}
+
+/// Validator from the validator set
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validator {
+    /// Ed25519 public key
+    pub public_key: [u8; 32],
+    pub weight: u64,
+    /// ADNL address of `validator_addr`, `None` for plain `validator` descriptions
+    pub adnl_addr: Option<Int256>,
+}
+
+impl Validator {
+    /// Short id of the public key, which identifies the validator in signature sets
+    pub fn node_id_short(&self) -> Int256 {
+        let mut hasher = Sha256::new();
+        hasher.update(PUB_ED25519_ID.to_le_bytes());
+        hasher.update(self.public_key);
+        Int256(hasher.finalize().into())
+    }
+}
+
+/// Validator set from config params 32 (previous), 34 (current) or 36 (next)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatorSet {
+    pub utime_since: u32,
+    pub utime_until: u32,
+    pub total: u16,
+    /// Number of validators of masterchain blocks
+    pub main: u16,
+    pub total_weight: u64,
+    pub list: Vec<Validator>,
+}
+
+impl ValidatorSet {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        let tag = slice.load_uint(8)?;
+        let utime_since = slice.load_uint(32)? as u32;
+        let utime_until = slice.load_uint(32)? as u32;
+        let total = slice.load_uint(16)? as u16;
+        let main = slice.load_uint(16)? as u16;
+        if main == 0 || main > total {
+            return Err(BocError::InvalidCell("invalid number of main validators"));
+        }
+        // validators#11 keeps Hashmap 16 inline, validators_ext#12 adds total_weight and keeps HashmapE 16
+        let (list, total_weight) = match tag {
+            VALIDATORS_TAG => (slice, None),
+            VALIDATORS_EXT_TAG => {
+                let total_weight = slice.load_uint(64)?;
+                let list = load_maybe_reference(&mut slice)?.ok_or(BocError::InvalidCell("empty validator set"))?;
+                (list.parse()?, Some(total_weight))
+            }
+            _ => return Err(BocError::InvalidCell("invalid validator set tag")),
+        };
+        let mut validators = Vec::with_capacity(total as usize);
+        for i in 0..total {
+            let descr = dict_get_slice(list.clone(), &i.to_be_bytes(), 16)?;
+            let mut descr = descr.ok_or(BocError::InvalidCell("missing validator"))?;
+            let tag = descr.load_uint(8)?;
+            if tag != VALIDATOR_TAG && tag != VALIDATOR_ADDR_TAG {
+                return Err(BocError::InvalidCell("invalid validator tag"));
+            }
+            if descr.load_uint(32)? != ED25519_PUBKEY_TAG {
+                return Err(BocError::InvalidCell("invalid validator public key tag"));
+            }
+            let public_key = descr.load_bytes(32)?.try_into().unwrap();
+            let weight = descr.load_uint(64)?;
+            let adnl_addr = if tag == VALIDATOR_ADDR_TAG { Some(load_int256(&mut descr)?) } else { None };
+            validators.push(Validator { public_key, weight, adnl_addr });
+        }
+        let total_weight = total_weight.unwrap_or_else(|| validators.iter().map(|validator| validator.weight).sum());
+        Ok(Self { utime_since, utime_until, total, main, total_weight, list: validators })
+    }
+
+    /// Validators of masterchain blocks, which are the first `main` validators of the set.
+    ///
+    /// The order is the one of the set, use [`Self::masterchain_validators`] for the order of the catchain.
+    pub fn masterchain(&self) -> &[Validator] {
+        &self.list[..self.main as usize]
+    }
+
+    /// Validators of masterchain blocks of the catchain `cc_seqno` in the order of the catchain.
+    ///
+    /// With `shuffle_mc_validators` the first `main` validators are shuffled with the pseudorandom
+    /// generator of the reference implementation, without it they are taken as is.
+    pub fn masterchain_validators(&self, catchain: &CatchainConfig, cc_seqno: u32) -> Vec<Validator> {
+        let validators = self.masterchain();
+        if !catchain.shuffle_mc_validators {
+            return validators.to_vec();
+        }
+        let mut prng = ValidatorSetPrng::new(-1, 1 << 63, cc_seqno);
+        let mut indexes = vec![0; validators.len()];
+        // the first draw is always 0, but it takes its number from the generator as in the reference
+        for i in 0..indexes.len() {
+            let j = prng.next_ranged(i as u64 + 1) as usize;
+            indexes[i] = indexes[j];
+            indexes[j] = i;
+        }
+        indexes.into_iter().map(|i| validators[i].clone()).collect()
+    }
+}
+
+/// Pseudorandom generator of validator subsets, SHA-512 of a zero seed incremented after each
+/// hash, the shard and the catchain seqno
+struct ValidatorSetPrng {
+    data: [u8; 48],
+    hash: [u8; 64],
+    pos: usize,
+}
+
+impl ValidatorSetPrng {
+    fn new(workchain: i32, shard: u64, cc_seqno: u32) -> Self {
+        let mut data = [0; 48];
+        data[32..40].copy_from_slice(&shard.to_be_bytes());
+        data[40..44].copy_from_slice(&workchain.to_be_bytes());
+        data[44..48].copy_from_slice(&cc_seqno.to_be_bytes());
+        Self { data, hash: [0; 64], pos: 8 }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if self.pos == 8 {
+            self.hash = Sha512::digest(self.data).into();
+            // increment the big-endian seed
+            for byte in self.data[..32].iter_mut().rev() {
+                *byte = byte.wrapping_add(1);
+                if *byte != 0 {
+                    break;
+                }
+            }
+            self.pos = 0;
+        }
+        let value = u64::from_be_bytes(self.hash[self.pos * 8..self.pos * 8 + 8].try_into().unwrap());
+        self.pos += 1;
+        value
+    }
+
+    /// Number in `0..range`
+    fn next_ranged(&mut self, range: u64) -> u64 {
+        ((self.next_u64() as u128 * range as u128) >> 64) as u64
+    }
+}
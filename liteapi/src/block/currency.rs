@@ -0,0 +1,26 @@
+use crate::boc::{dict_entries, CellSlice};
+
+use super::{load_maybe_reference, Result};
+
+/// Amount of nanotons and extra currencies
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CurrencyCollection {
+    pub grams: u128,
+    /// Amounts of extra currencies by their ids
+    pub other: Vec<(u32, u128)>,
+}
+
+impl CurrencyCollection {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        let grams = slice.load_coins()?;
+        // extra_currencies$_ dict:(HashmapE 32 (VarUInteger 32))
+        let other = match load_maybe_reference(slice)? {
+            Some(root) => dict_entries(&root, 32)?
+                .into_iter()
+                .map(|(key, mut value)| Ok((u32::from_be_bytes(key.try_into().unwrap()), value.load_var_uint(5)?)))
+                .collect::<Result<_>>()?,
+            None => Vec::new(),
+        };
+        Ok(Self { grams, other })
+    }
+}
@@ -0,0 +1,129 @@
+use crate::boc::{Cell, CellSlice};
+use crate::tl::common::AccountId;
+
+use super::{load_int256, CurrencyCollection, Result};
+
+/// Address of message source or destination
+#[derive(Debug, Clone, PartialEq)]
+pub enum MsgAddress {
+    /// `addr_none`
+    None,
+    /// `addr_extern` of `bits` bits
+    External { bits: u16, address: Vec<u8> },
+    /// `addr_std`, anycast is ignored
+    Std(AccountId),
+    /// `addr_var` of `bits` bits, anycast is ignored
+    Var { workchain: i32, bits: u16, address: Vec<u8> },
+}
+
+impl MsgAddress {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        Ok(match slice.load_uint(2)? {
+            0b00 => Self::None,
+            0b01 => {
+                let bits = slice.load_uint(9)? as u16;
+                Self::External { bits, address: slice.load_bits(bits as usize)? }
+            }
+            0b10 => {
+                skip_anycast(slice)?;
+                let workchain = slice.load_int(8)? as i32;
+                Self::Std(AccountId { workchain, id: load_int256(slice)? })
+            }
+            _ => {
+                skip_anycast(slice)?;
+                let bits = slice.load_uint(9)? as u16;
+                let workchain = slice.load_int(32)? as i32;
+                Self::Var { workchain, bits, address: slice.load_bits(bits as usize)? }
+            }
+        })
+    }
+}
+
+/// Skips `Maybe Anycast`
+fn skip_anycast(slice: &mut CellSlice) -> Result<()> {
+    if slice.load_bit()? {
+        // anycast_info$_ depth:(#<= 30) rewrite_pfx:(bits depth)
+        let depth = slice.load_uint(5)? as usize;
+        slice.skip_bits(depth)?;
+    }
+    Ok(())
+}
+
+/// Header of message, `CommonMsgInfo`
+#[derive(Debug, Clone, PartialEq)]
+pub enum MsgInfo {
+    Internal {
+        ihr_disabled: bool,
+        bounce: bool,
+        bounced: bool,
+        src: MsgAddress,
+        dest: MsgAddress,
+        value: CurrencyCollection,
+        ihr_fee: u128,
+        fwd_fee: u128,
+        created_lt: u64,
+        created_at: u32,
+    },
+    ExternalIn {
+        src: MsgAddress,
+        dest: MsgAddress,
+        import_fee: u128,
+    },
+    ExternalOut {
+        src: MsgAddress,
+        dest: MsgAddress,
+        created_lt: u64,
+        created_at: u32,
+    },
+}
+
+impl MsgInfo {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        if !slice.load_bit()? {
+            // int_msg_info$0
+            return Ok(Self::Internal {
+                ihr_disabled: slice.load_bit()?,
+                bounce: slice.load_bit()?,
+                bounced: slice.load_bit()?,
+                src: MsgAddress::parse(slice)?,
+                dest: MsgAddress::parse(slice)?,
+                value: CurrencyCollection::parse(slice)?,
+                ihr_fee: slice.load_coins()?,
+                fwd_fee: slice.load_coins()?,
+                created_lt: slice.load_uint(64)?,
+                created_at: slice.load_uint(32)? as u32,
+            });
+        }
+        if !slice.load_bit()? {
+            // ext_in_msg_info$10
+            return Ok(Self::ExternalIn { src: MsgAddress::parse(slice)?, dest: MsgAddress::parse(slice)?, import_fee: slice.load_coins()? });
+        }
+        // ext_out_msg_info$11
+        Ok(Self::ExternalOut {
+            src: MsgAddress::parse(slice)?,
+            dest: MsgAddress::parse(slice)?,
+            created_lt: slice.load_uint(64)?,
+            created_at: slice.load_uint(32)? as u32,
+        })
+    }
+}
+
+/// Message with decoded header, its state init and body are left in the cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub info: MsgInfo,
+    pub cell: Cell,
+}
+
+impl Message {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let info = MsgInfo::parse(&mut cell.parse()?)?;
+        Ok(Self { info, cell: cell.clone() })
+    }
+
+    /// Hash of the message cell
+    pub fn hash(&self) -> [u8; 32] {
+        self.cell.repr_hash()
+    }
+}
+
@@ -0,0 +1,36 @@
+//! Typed decoding of blockchain structures stored in cells
+mod account;
+mod block_data;
+mod config;
+mod currency;
+mod message;
+mod msg_descr;
+mod shard;
+#[cfg(test)]
+mod tests;
+mod transaction;
+
+use crate::boc::{BocError, Cell, CellSlice};
+use crate::tl::common::Int256;
+
+pub use account::{Account, AccountStorageState, StateInit};
+pub use block_data::{AccountBlock, Block, BlockExtra, BlockInfo, ExtBlkRef, ValueFlow};
+pub use config::{BlockchainConfig, CatchainConfig, ConfigParam, GasPrices, GlobalVersion, MsgForwardPrices, StoragePrices, Validator, ValidatorSet, WorkchainDescr};
+pub use currency::CurrencyCollection;
+pub use message::{Message, MsgAddress, MsgInfo};
+pub use msg_descr::{InMsg, InMsgKind, OutMsg, OutMsgKind};
+pub use shard::{ShardDescr, ShardHashes, ShardIdent, ShardTree, MAX_SPLIT_DEPTH};
+pub use transaction::{
+    AccStatusChange, AccountStatus, ActionPhase, BouncePhase, ComputePhase, ComputeSkipReason, ComputeVm, CreditPhase, StoragePhase, StorageUsed, Transaction,
+    TransactionDescr, TransactionKind,
+};
+
+type Result<T> = std::result::Result<T, BocError>;
+
+fn load_maybe_reference(slice: &mut CellSlice) -> Result<Option<Cell>> {
+    Ok(if slice.load_bit()? { Some(slice.load_reference()?) } else { None })
+}
+
+fn load_int256(slice: &mut CellSlice) -> Result<Int256> {
+    Ok(Int256(slice.load_bytes(32)?.try_into().unwrap()))
+}
@@ -0,0 +1,160 @@
+use crate::boc::{BocError, Cell, CellSlice};
+use crate::tl::common::Int256;
+
+use super::{Message, Result, Transaction};
+
+/// Kind of inbound message descriptor, `InMsg`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InMsgKind {
+    /// `msg_import_ext$000`
+    External,
+    /// `msg_import_ihr$010`
+    Ihr,
+    /// `msg_import_imm$011`, internal message created in the same block
+    Immediate,
+    /// `msg_import_fin$100`, internal message that reached its destination
+    Final,
+    /// `msg_import_tr$101`, internal message in transit
+    Transit,
+    /// `msg_discard_fin$110`
+    DiscardedFinal,
+    /// `msg_discard_tr$111`
+    DiscardedTransit,
+    /// `msg_import_deferred_fin$00100`
+    DeferredFinal,
+    /// `msg_import_deferred_tr$00101`
+    DeferredTransit,
+}
+
+/// Inbound message descriptor of `InMsgDescr`, the message and its transaction are left in cells
+#[derive(Debug, Clone, PartialEq)]
+pub struct InMsg {
+    /// Hash of the message, the key of the descriptor
+    pub hash: Int256,
+    pub kind: InMsgKind,
+    pub message: Cell,
+    /// Transaction processing the message, if it was processed in this block
+    pub transaction: Option<Cell>,
+}
+
+impl InMsg {
+    pub fn parse(hash: Int256, slice: &mut CellSlice) -> Result<Self> {
+        let kind = match slice.load_uint(3)? {
+            0b000 => InMsgKind::External,
+            0b001 => match slice.load_uint(2)? {
+                0b00 => InMsgKind::DeferredFinal,
+                0b01 => InMsgKind::DeferredTransit,
+                _ => return Err(BocError::InvalidCell("invalid inbound message tag")),
+            },
+            0b010 => InMsgKind::Ihr,
+            0b011 => InMsgKind::Immediate,
+            0b100 => InMsgKind::Final,
+            0b101 => InMsgKind::Transit,
+            0b110 => InMsgKind::DiscardedFinal,
+            _ => InMsgKind::DiscardedTransit,
+        };
+        let first = slice.load_reference()?;
+        let message = match kind {
+            InMsgKind::External | InMsgKind::Ihr => first,
+            _ => envelope_message(&first)?,
+        };
+        let transaction = match kind {
+            InMsgKind::External | InMsgKind::Ihr | InMsgKind::Immediate | InMsgKind::Final | InMsgKind::DeferredFinal => Some(slice.load_reference()?),
+            _ => None,
+        };
+        Ok(Self { hash, kind, message, transaction })
+    }
+
+    pub fn message(&self) -> Result<Message> {
+        Message::parse(&self.message)
+    }
+
+    pub fn transaction(&self) -> Result<Option<Transaction>> {
+        self.transaction.as_ref().map(Transaction::parse).transpose()
+    }
+}
+
+/// Kind of outbound message descriptor, `OutMsg`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutMsgKind {
+    /// `msg_export_ext$000`
+    External,
+    /// `msg_export_new$001`, internal message created in this block
+    New,
+    /// `msg_export_imm$010`, internal message imported in the same block
+    Immediate,
+    /// `msg_export_tr$011`, internal message in transit
+    Transit,
+    /// `msg_export_deq_imm$100`
+    DequeueImmediate,
+    /// `msg_export_deq$1100`
+    Dequeue,
+    /// `msg_export_deq_short$1101`, keeps only the hash of the envelope
+    DequeueShort,
+    /// `msg_export_tr_req$111`
+    TransitRequeued,
+    /// `msg_export_new_defer$10100`
+    NewDeferred,
+    /// `msg_export_deferred_tr$10101`
+    DeferredTransit,
+}
+
+/// Outbound message descriptor of `OutMsgDescr`, the message and its transaction are left in cells
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutMsg {
+    /// Hash of the message, the key of the descriptor
+    pub hash: Int256,
+    pub kind: OutMsgKind,
+    /// Message, `None` for [`OutMsgKind::DequeueShort`]
+    pub message: Option<Cell>,
+    /// Transaction creating the message, if it was created in this block
+    pub transaction: Option<Cell>,
+}
+
+impl OutMsg {
+    pub fn parse(hash: Int256, slice: &mut CellSlice) -> Result<Self> {
+        let kind = match slice.load_uint(3)? {
+            0b000 => OutMsgKind::External,
+            0b001 => OutMsgKind::New,
+            0b010 => OutMsgKind::Immediate,
+            0b011 => OutMsgKind::Transit,
+            0b100 => OutMsgKind::DequeueImmediate,
+            0b101 => match slice.load_uint(2)? {
+                0b00 => OutMsgKind::NewDeferred,
+                0b01 => OutMsgKind::DeferredTransit,
+                _ => return Err(BocError::InvalidCell("invalid outbound message tag")),
+            },
+            0b110 => {
+                if slice.load_bit()? {
+                    OutMsgKind::DequeueShort
+                } else {
+                    OutMsgKind::Dequeue
+                }
+            }
+            _ => OutMsgKind::TransitRequeued,
+        };
+        let message = match kind {
+            OutMsgKind::DequeueShort => None,
+            OutMsgKind::External => Some(slice.load_reference()?),
+            _ => Some(envelope_message(&slice.load_reference()?)?),
+        };
+        let transaction = match kind {
+            OutMsgKind::External | OutMsgKind::New | OutMsgKind::Immediate | OutMsgKind::NewDeferred => Some(slice.load_reference()?),
+            _ => None,
+        };
+        Ok(Self { hash, kind, message, transaction })
+    }
+
+    pub fn message(&self) -> Result<Option<Message>> {
+        self.message.as_ref().map(Message::parse).transpose()
+    }
+
+    pub fn transaction(&self) -> Result<Option<Transaction>> {
+        self.transaction.as_ref().map(Transaction::parse).transpose()
+    }
+}
+
+/// Message of `MsgEnvelope`, the only reference of both `msg_envelope#4` and `msg_envelope_v2#5`
+fn envelope_message(envelope: &Cell) -> Result<Cell> {
+    envelope.reference(0).cloned().ok_or(BocError::InvalidCell("message envelope without message"))
+}
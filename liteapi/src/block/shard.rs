@@ -0,0 +1,273 @@
+use std::fmt;
+
+use crate::boc::{dict_entries, BocError, Cell, CellSlice};
+use crate::tl::common::{AccountId, BlockIdExt, Int256};
+
+use super::{load_int256, load_maybe_reference, Result};
+
+const SHARD_FULL: u64 = 1 << 63;
+/// Maximum depth of shard splits
+pub const MAX_SPLIT_DEPTH: u8 = 60;
+
+/// Shard of a workchain, `shard` is the prefix of account ids followed by a tag bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShardIdent {
+    workchain: i32,
+    shard: u64,
+}
+
+impl ShardIdent {
+    /// `None` if the shard is not a valid shard id
+    pub fn new(workchain: i32, shard: u64) -> Option<Self> {
+        if shard == 0 || shard.trailing_zeros() < 63 - MAX_SPLIT_DEPTH as u32 {
+            return None;
+        }
+        Some(Self { workchain, shard })
+    }
+
+    /// Shard containing the whole workchain
+    pub fn full(workchain: i32) -> Self {
+        Self { workchain, shard: SHARD_FULL }
+    }
+
+    /// Shard of `prefix_len` bits containing the account, `None` if it is deeper than
+    /// [`MAX_SPLIT_DEPTH`]
+    pub fn with_account(account: &AccountId, prefix_len: u8) -> Option<Self> {
+        if prefix_len > MAX_SPLIT_DEPTH {
+            return None;
+        }
+        let tag = SHARD_FULL >> prefix_len;
+        let prefix = account_prefix(account) & !(tag | (tag - 1));
+        Some(Self { workchain: account.workchain, shard: prefix | tag })
+    }
+
+    /// Decodes `shard_ident$00 shard_pfx_bits:(#<= 60) workchain_id:int32 shard_prefix:uint64`
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        if slice.load_uint(2)? != 0 {
+            return Err(BocError::InvalidCell("invalid shard ident tag"));
+        }
+        let prefix_len = slice.load_uint(6)? as u8;
+        let workchain = slice.load_int(32)? as i32;
+        let prefix = slice.load_uint(64)?;
+        if prefix_len > MAX_SPLIT_DEPTH {
+            return Err(BocError::InvalidCell("invalid shard prefix length"));
+        }
+        let tag = SHARD_FULL >> prefix_len;
+        Ok(Self { workchain, shard: (prefix & !(tag | (tag - 1))) | tag })
+    }
+
+    /// Shard of the block
+    pub fn of_block(block: &BlockIdExt) -> Option<Self> {
+        Self::new(block.workchain, block.shard)
+    }
+
+    pub fn workchain(&self) -> i32 {
+        self.workchain
+    }
+
+    pub fn shard(&self) -> u64 {
+        self.shard
+    }
+
+    /// Number of bits in the prefix of account ids
+    pub fn prefix_len(&self) -> u8 {
+        63 - self.shard.trailing_zeros() as u8
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.shard == SHARD_FULL
+    }
+
+    /// Shard this one was split from, `None` for the full shard
+    pub fn parent(&self) -> Option<Self> {
+        if self.is_full() {
+            return None;
+        }
+        let tag = self.tag();
+        Some(Self { workchain: self.workchain, shard: (self.shard - tag) | (tag << 1) })
+    }
+
+    /// Left and right shards after split, `None` if the shard can not be split further
+    pub fn split(&self) -> Option<(Self, Self)> {
+        if self.prefix_len() >= MAX_SPLIT_DEPTH {
+            return None;
+        }
+        let half = self.tag() >> 1;
+        Some((Self { shard: self.shard - half, ..*self }, Self { shard: self.shard + half, ..*self }))
+    }
+
+    /// Shard made by merging this shard with its sibling `other`, `None` if they are not siblings
+    pub fn merge(&self, other: &Self) -> Option<Self> {
+        let parent = self.parent()?;
+        (self != other && other.parent() == Some(parent)).then_some(parent)
+    }
+
+    /// Whether `other` is this shard or a shard split from it
+    pub fn contains(&self, other: &Self) -> bool {
+        self.workchain == other.workchain && self.tag() >= other.tag() && (self.shard ^ other.shard) & self.prefix_mask() == 0
+    }
+
+    pub fn contains_account(&self, account: &AccountId) -> bool {
+        self.workchain == account.workchain && (self.shard ^ account_prefix(account)) & self.prefix_mask() == 0
+    }
+
+    fn tag(&self) -> u64 {
+        self.shard & self.shard.wrapping_neg()
+    }
+
+    /// Mask of the prefix bits
+    fn prefix_mask(&self) -> u64 {
+        !((self.tag() << 1).wrapping_sub(1))
+    }
+}
+
+impl fmt::Display for ShardIdent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{:X})", self.workchain, self.shard)
+    }
+}
+
+fn account_prefix(account: &AccountId) -> u64 {
+    u64::from_be_bytes(account.id.0[..8].try_into().unwrap())
+}
+
+/// Description of the top block of a shard kept in masterchain state and blocks, fees and
+/// future split/merge are not decoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardDescr {
+    pub seqno: u32,
+    /// Seqno of masterchain block the shard block was registered in
+    pub reg_mc_seqno: u32,
+    pub start_lt: u64,
+    pub end_lt: u64,
+    pub root_hash: Int256,
+    pub file_hash: Int256,
+    pub before_split: bool,
+    pub before_merge: bool,
+    pub want_split: bool,
+    pub want_merge: bool,
+    pub nx_cc_updated: bool,
+    pub next_catchain_seqno: u32,
+    pub next_validator_shard: u64,
+    pub min_ref_mc_seqno: u32,
+    pub gen_utime: u32,
+}
+
+impl ShardDescr {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        // shard_descr#b keeps fees inline, shard_descr_new#a keeps them in a reference
+        let tag = slice.load_uint(4)?;
+        if tag != 0xa && tag != 0xb {
+            return Err(BocError::InvalidCell("invalid shard description tag"));
+        }
+        let seqno = slice.load_uint(32)? as u32;
+        let reg_mc_seqno = slice.load_uint(32)? as u32;
+        let start_lt = slice.load_uint(64)?;
+        let end_lt = slice.load_uint(64)?;
+        let root_hash = load_int256(slice)?;
+        let file_hash = load_int256(slice)?;
+        let before_split = slice.load_bit()?;
+        let before_merge = slice.load_bit()?;
+        let want_split = slice.load_bit()?;
+        let want_merge = slice.load_bit()?;
+        let nx_cc_updated = slice.load_bit()?;
+        // flags
+        slice.skip_bits(3)?;
+        Ok(Self {
+            seqno,
+            reg_mc_seqno,
+            start_lt,
+            end_lt,
+            root_hash,
+            file_hash,
+            before_split,
+            before_merge,
+            want_split,
+            want_merge,
+            nx_cc_updated,
+            next_catchain_seqno: slice.load_uint(32)? as u32,
+            next_validator_shard: slice.load_uint(64)?,
+            min_ref_mc_seqno: slice.load_uint(32)? as u32,
+            gen_utime: slice.load_uint(32)? as u32,
+        })
+    }
+
+    /// Id of the shard block described
+    pub fn block_id(&self, shard: &ShardIdent) -> BlockIdExt {
+        BlockIdExt {
+            workchain: shard.workchain,
+            shard: shard.shard,
+            seqno: self.seqno,
+            root_hash: self.root_hash.clone(),
+            file_hash: self.file_hash.clone(),
+        }
+    }
+}
+
+/// Shards of workchain, the leaves of its shard tree in the order of shard ids
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardTree {
+    pub workchain: i32,
+    pub shards: Vec<(ShardIdent, ShardDescr)>,
+}
+
+impl ShardTree {
+    /// Decodes `BinTree ShardDescr` of the workchain
+    pub fn parse(workchain: i32, root: &Cell) -> Result<Self> {
+        let mut shards = Vec::new();
+        let mut stack = vec![(root.clone(), ShardIdent::full(workchain))];
+        while let Some((cell, shard)) = stack.pop() {
+            let mut slice = cell.parse()?;
+            if !slice.load_bit()? {
+                // bt_leaf$0
+                shards.push((shard, ShardDescr::parse(&mut slice)?));
+                continue;
+            }
+            // bt_fork$1, the left branch is pushed last to be visited first
+            let (left, right) = shard.split().ok_or(BocError::InvalidCell("shard tree is too deep"))?;
+            let left_cell = slice.load_reference()?;
+            stack.push((slice.load_reference()?, right));
+            stack.push((left_cell, left));
+        }
+        Ok(Self { workchain, shards })
+    }
+
+    /// Shard containing the account with the description of its top block
+    pub fn find(&self, account: &AccountId) -> Option<&(ShardIdent, ShardDescr)> {
+        self.shards.iter().find(|(shard, _)| shard.contains_account(account))
+    }
+}
+
+/// Shard trees of all workchains
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardHashes {
+    pub workchains: Vec<ShardTree>,
+}
+
+impl ShardHashes {
+    /// Decodes `ShardHashes` at the start of the cell, such as `data` of `liteServer.allShardsInfo`
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        Self::load(&mut cell.parse()?)
+    }
+
+    /// Decodes `shard_hashes:(HashmapE 32 ^(BinTree ShardDescr))` from the slice
+    pub fn load(slice: &mut CellSlice) -> Result<Self> {
+        let Some(root) = load_maybe_reference(slice)? else {
+            return Ok(Self { workchains: Vec::new() });
+        };
+        let workchains = dict_entries(&root, 32)?
+            .into_iter()
+            .map(|(key, mut value)| ShardTree::parse(i32::from_be_bytes(key.try_into().unwrap()), &value.load_reference()?))
+            .collect::<Result<_>>()?;
+        Ok(Self { workchains })
+    }
+
+    pub fn workchain(&self, workchain: i32) -> Option<&ShardTree> {
+        self.workchains.iter().find(|tree| tree.workchain == workchain)
+    }
+
+    /// Shard containing the account with the description of its top block
+    pub fn find(&self, account: &AccountId) -> Option<&(ShardIdent, ShardDescr)> {
+        self.workchain(account.workchain)?.find(account)
+    }
+}
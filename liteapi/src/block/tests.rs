@@ -0,0 +1,542 @@
+use std::error::Error;
+
+use crate::boc::{key_bits, make_dict, BocWriter, CellBuilder};
+use crate::tl::common::{AccountId, BlockIdExt};
+use crate::tl::response::TransactionList;
+
+use super::*;
+
+type TestResult = std::result::Result<(), Box<dyn Error>>;
+
+fn config(params: Vec<(u32, Cell)>) -> Result<BlockchainConfig> {
+    let entries = params
+        .into_iter()
+        .map(|(index, value)| Ok((key_bits(&index.to_be_bytes(), 32), CellBuilder::new().store_reference(value)?.build()?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(BlockchainConfig::new(make_dict(&entries, 32)?))
+}
+
+fn workchain() -> Result<Cell> {
+    CellBuilder::new()
+        .store_uint(0xa6, 8)?
+        .store_uint(1573821854, 32)?
+        .store_uint(0, 8)?
+        .store_uint(0, 8)?
+        .store_uint(8, 8)?
+        .store_bit(true)?
+        .store_bit(true)?
+        .store_bit(true)?
+        .store_uint(0, 13)?
+        .store_bytes(&[1; 32])?
+        .store_bytes(&[2; 32])?
+        .store_uint(0, 32)?
+        .store_uint(1, 4)?
+        .store_uint(0, 32)?
+        .store_uint(0, 64)?
+        .build()
+}
+
+fn validator_set() -> Result<Cell> {
+    let descr = |tag: u64, key: u8| -> Result<Cell> {
+        let mut builder = CellBuilder::new();
+        builder.store_uint(tag, 8)?.store_uint(0x8e81278a, 32)?.store_bytes(&[key; 32])?.store_uint(key as u64, 64)?;
+        if tag == 0x73 {
+            builder.store_bytes(&[key; 32])?;
+        }
+        builder.build()
+    };
+    let list = make_dict(&[(key_bits(&[0, 0], 16), descr(0x53, 1)?), (key_bits(&[0, 1], 16), descr(0x73, 2)?)], 16)?;
+    let mut builder = CellBuilder::new();
+    builder.store_uint(0x11, 8)?.store_uint(100, 32)?.store_uint(200, 32)?.store_uint(2, 16)?.store_uint(1, 16)?;
+    builder.store_slice(&list.slice())?.build()
+}
+
+#[test]
+fn test_config_params() -> TestResult {
+    let address = |byte: u8| CellBuilder::new().store_bytes(&[byte; 32])?.build();
+    let workchains = make_dict(&[(key_bits(&0i32.to_be_bytes(), 32), workchain()?)], 32)?;
+    let storage_prices = CellBuilder::new()
+        .store_uint(0xcc, 8)?
+        .store_uint(0, 32)?
+        .store_uint(1, 64)?
+        .store_uint(500, 64)?
+        .store_uint(1000, 64)?
+        .store_uint(500000, 64)?
+        .build()?;
+    let mut gas = CellBuilder::new();
+    gas.store_uint(0xd1, 8)?.store_uint(100, 64)?.store_uint(1000000, 64)?.store_uint(0xde, 8)?;
+    for value in [26214400, 1000000, 70000000, 10000, 2500000, 100000000, 1000000000] {
+        gas.store_uint(value, 64)?;
+    }
+    let msg_forward = CellBuilder::new()
+        .store_uint(0xea, 8)?
+        .store_uint(400000, 64)?
+        .store_uint(26214400, 64)?
+        .store_uint(2621440000, 64)?
+        .store_uint(98304, 32)?
+        .store_uint(21845, 16)?
+        .store_uint(21845, 16)?
+        .build()?;
+    let config = config(vec![
+        (0, address(5)?),
+        (1, address(3)?),
+        (8, CellBuilder::new().store_uint(0xc4, 8)?.store_uint(9, 32)?.store_uint(494, 64)?.build()?),
+        (12, CellBuilder::new().store_bit(true)?.store_reference(workchains)?.build()?),
+        (18, make_dict(&[(key_bits(&[0; 4], 32), storage_prices)], 32)?),
+        (20, gas.build()?),
+        (24, msg_forward),
+        (28, CellBuilder::new().store_uint(0xc2, 8)?.store_uint(1, 8)?.store_uint(1000, 32)?.store_uint(2000, 32)?.store_uint(3000, 32)?.store_uint(7, 32)?.build()?),
+        (34, validator_set()?),
+        (71, address(7)?),
+    ])?;
+
+    assert_eq!(config.config_address()?, Some(Int256([5; 32])));
+    assert_eq!(config.elector_address()?, Some(Int256([3; 32])));
+    assert_eq!(config.global_version()?, Some(GlobalVersion { version: 9, capabilities: 494 }));
+    let workchains = config.workchains()?.ok_or("missing workchains")?;
+    assert_eq!(workchains.len(), 1);
+    assert_eq!(workchains[0].0, 0);
+    assert_eq!((workchains[0].1.max_split, workchains[0].1.zerostate_file_hash.0), (8, [2; 32]));
+    assert_eq!(config.storage_prices()?.ok_or("missing storage prices")?[0].mc_cell_price_ps, 500000);
+    let gas = config.gas_prices(true)?.ok_or("missing gas prices")?;
+    assert_eq!((gas.flat_gas_limit, gas.gas_price, gas.special_gas_limit, gas.delete_due_limit), (100, 26214400, 70000000, 1000000000));
+    assert!(config.gas_prices(false)?.is_none());
+    assert_eq!(config.msg_forward_prices(true)?.ok_or("missing forward prices")?.first_frac, 21845);
+
+    let validators = config.validator_set(34)?.ok_or("missing validator set")?;
+    assert_eq!((validators.utime_since, validators.total, validators.main, validators.total_weight), (100, 2, 1, 3));
+    assert_eq!(validators.list[0].adnl_addr, None);
+    assert_eq!(validators.list[1].adnl_addr, Some(Int256([2; 32])));
+    assert_eq!(validators.masterchain().len(), 1);
+    let catchain = config.catchain_config()?.ok_or("missing catchain config")?;
+    assert!(catchain.shuffle_mc_validators);
+    assert_eq!((catchain.mc_catchain_lifetime, catchain.shard_validators_lifetime, catchain.shard_validators_num), (1000, 3000, 7));
+
+    let params = config.params()?;
+    let indexes: Vec<_> = params.iter().map(|(index, _)| *index).collect();
+    assert_eq!(indexes, [0, 1, 8, 12, 18, 20, 24, 28, 34, 71]);
+    assert!(matches!(params[8].1, ConfigParam::ValidatorSet(_)));
+    assert!(matches!(params[9].1, ConfigParam::Raw(_)));
+    assert!(config.get(7)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_masterchain_validators() -> TestResult {
+    let validator = |key: u8| Validator { public_key: [key; 32], weight: key as u64, adnl_addr: None };
+    let set = ValidatorSet { utime_since: 0, utime_until: 0, total: 16, main: 12, total_weight: 136, list: (1..=16).map(validator).collect() };
+    let catchain = |shuffle_mc_validators| CatchainConfig {
+        shuffle_mc_validators,
+        mc_catchain_lifetime: 250,
+        shard_catchain_lifetime: 250,
+        shard_validators_lifetime: 1000,
+        shard_validators_num: 7,
+    };
+    assert_eq!(set.masterchain_validators(&catchain(false), 1), set.masterchain());
+
+    // shuffling changes the order of the first `main` validators, not the validators themselves
+    let keys = |validators: Vec<Validator>| validators.iter().map(|v| v.public_key[0]).collect::<Vec<_>>();
+    let orders: Vec<_> = (0..10).map(|cc_seqno| keys(set.masterchain_validators(&catchain(true), cc_seqno))).collect();
+    for order in &orders {
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (1..=12).collect::<Vec<_>>());
+    }
+    assert!(orders.iter().any(|order| order != &orders[0]));
+    // 12 draws take two hashes of the generator
+    assert_eq!(orders[7], [6, 8, 5, 11, 1, 10, 4, 12, 3, 7, 2, 9]);
+    Ok(())
+}
+
+fn shard_descr(seqno: u32) -> Result<CellBuilder> {
+    let mut builder = CellBuilder::new();
+    builder
+        .store_bit(false)?
+        .store_uint(0xa, 4)?
+        .store_uint(seqno as u64, 32)?
+        .store_uint(1, 32)?
+        .store_uint(1000, 64)?
+        .store_uint(2000, 64)?
+        .store_bytes(&[seqno as u8; 32])?
+        .store_bytes(&[!seqno as u8; 32])?
+        .store_uint(0b00100, 5)?
+        .store_uint(0, 3)?
+        .store_uint(7, 32)?
+        .store_uint(0, 64)?
+        .store_uint(1, 32)?
+        .store_uint(1700000000, 32)?
+        .store_bit(false)?
+        .store_reference(CellBuilder::new().build()?)?;
+    Ok(builder)
+}
+
+#[test]
+fn test_shards() -> TestResult {
+    let full = ShardIdent::full(0);
+    let (left, right) = full.split().ok_or("full shard is not split")?;
+    assert_eq!((left.shard(), right.shard()), (0x4000000000000000, 0xc000000000000000));
+    assert_eq!((left.parent(), full.parent()), (Some(full), None));
+    assert_eq!(left.merge(&right), Some(full));
+    assert_eq!(left.merge(&left), None);
+    let (right_left, _) = right.split().ok_or("shard is not split")?;
+    assert_eq!(right_left.shard(), 0xa000000000000000);
+    assert_eq!(right_left.prefix_len(), 2);
+    assert!(full.contains(&right_left) && right.contains(&right_left) && !left.contains(&right_left));
+    assert_eq!(right_left.merge(&left), None);
+    assert!(ShardIdent::new(0, 0).is_none() && ShardIdent::new(0, 1).is_none());
+
+    let mut id = [0; 32];
+    id[0] = 0xa5;
+    let account = AccountId { workchain: 0, id: Int256(id) };
+    assert!(right_left.contains_account(&account) && !left.contains_account(&account));
+    assert_eq!(ShardIdent::with_account(&account, 2), Some(right_left));
+    assert_eq!(ShardIdent::with_account(&account, 0), Some(full));
+    assert!(!ShardIdent::full(-1).contains_account(&account));
+
+    // left shard and the right shard split into two
+    let right_tree = CellBuilder::new()
+        .store_bit(true)?
+        .store_reference(shard_descr(2)?.build()?)?
+        .store_reference(shard_descr(3)?.build()?)?
+        .build()?;
+    let tree = CellBuilder::new().store_bit(true)?.store_reference(shard_descr(1)?.build()?)?.store_reference(right_tree)?.build()?;
+    let workchains = make_dict(&[(key_bits(&0i32.to_be_bytes(), 32), CellBuilder::new().store_reference(tree)?.build()?)], 32)?;
+    let hashes = ShardHashes::parse(&CellBuilder::new().store_bit(true)?.store_reference(workchains)?.build()?)?;
+    let shards = &hashes.workchain(0).ok_or("missing workchain")?.shards;
+    let ids: Vec<_> = shards.iter().map(|(shard, descr)| (shard.shard(), descr.seqno)).collect();
+    assert_eq!(ids, [(0x4000000000000000, 1), (0xa000000000000000, 2), (0xe000000000000000, 3)]);
+    let (shard, descr) = hashes.find(&account).ok_or("account shard is not found")?;
+    assert_eq!(*shard, right_left);
+    assert_eq!((descr.reg_mc_seqno, descr.start_lt, descr.end_lt, descr.want_split, descr.gen_utime), (1, 1000, 2000, true, 1700000000));
+    let block = descr.block_id(shard);
+    assert_eq!((block.shard, block.seqno, block.root_hash.0), (0xa000000000000000, 2, [2; 32]));
+    assert!(hashes.find(&AccountId { workchain: 1, id: Int256(id) }).is_none());
+    Ok(())
+}
+
+fn std_address(builder: &mut CellBuilder, workchain: i8, byte: u8) -> Result<()> {
+    builder.store_uint(0b100, 3)?.store_int(workchain as i64, 8)?.store_bytes(&[byte; 32])?;
+    Ok(())
+}
+
+/// Ordinary transaction with external inbound message and one outbound internal message
+fn transaction(lt: u64) -> Result<Cell> {
+    let mut in_msg = CellBuilder::new();
+    in_msg.store_uint(0b10, 2)?.store_uint(0, 2)?;
+    std_address(&mut in_msg, 0, 1)?;
+    in_msg.store_coins(0)?.store_uint(0, 2)?;
+    let mut out_msg = CellBuilder::new();
+    out_msg.store_uint(0b0110, 4)?;
+    std_address(&mut out_msg, 0, 1)?;
+    std_address(&mut out_msg, -1, 2)?;
+    out_msg.store_coins(1_000_000_000)?.store_bit(false)?.store_coins(0)?.store_coins(666672)?;
+    out_msg.store_uint(lt + 1, 64)?.store_uint(1700000000, 32)?.store_uint(0, 2)?;
+    let out_msgs = make_dict(&[(key_bits(&[0, 0], 15), CellBuilder::new().store_reference(out_msg.build()?)?.build()?)], 15)?;
+    let messages = CellBuilder::new().store_bit(true)?.store_reference(in_msg.build()?)?.store_bit(true)?.store_reference(out_msgs)?.build()?;
+
+    let compute = CellBuilder::new()
+        .store_uint(2, 3)?
+        .store_uint(3308, 16)?
+        .store_uint(2, 3)?
+        .store_uint(10000, 16)?
+        .store_bit(true)?
+        .store_uint(2, 2)?
+        .store_uint(10000, 16)?
+        .store_int(0, 8)?
+        .store_int(-14, 32)?
+        .store_bit(false)?
+        .store_uint(68, 32)?
+        .store_bytes(&[3; 32])?
+        .store_bytes(&[4; 32])?
+        .build()?;
+    let action = CellBuilder::new()
+        .store_uint(0b110, 3)?
+        .store_bit(false)?
+        .store_bit(true)?
+        .store_coins(333328)?
+        .store_bit(false)?
+        .store_int(0, 32)?
+        .store_bit(false)?
+        .store_uint(1, 16)?
+        .store_uint(0, 16)?
+        .store_uint(0, 16)?
+        .store_uint(1, 16)?
+        .store_bytes(&[5; 32])?
+        .store_uint(1, 3)?
+        .store_uint(1, 8)?
+        .store_uint(1, 3)?
+        .store_uint(200, 8)?
+        .build()?;
+    let description = CellBuilder::new()
+        .store_uint(0, 4)?
+        .store_bit(false)?
+        .store_bit(true)?
+        .store_coins(25)?
+        .store_bit(false)?
+        .store_bit(false)?
+        .store_bit(false)?
+        .store_uint(0b1000, 4)?
+        .store_coins(3308000)?
+        .store_reference(compute)?
+        .store_bit(true)?
+        .store_reference(action)?
+        .store_bit(false)?
+        .store_bit(false)?
+        .store_bit(false)?
+        .build()?;
+
+    CellBuilder::new()
+        .store_uint(0b0111, 4)?
+        .store_bytes(&[1; 32])?
+        .store_uint(lt, 64)?
+        .store_bytes(&[9; 32])?
+        .store_uint(lt - 10, 64)?
+        .store_uint(1700000000, 32)?
+        .store_uint(1, 15)?
+        .store_uint(0b10, 2)?
+        .store_uint(0b10, 2)?
+        .store_reference(messages)?
+        .store_coins(3641333)?
+        .store_bit(false)?
+        .store_reference(CellBuilder::new().build()?)?
+        .store_reference(description)?
+        .build()
+}
+
+#[test]
+fn test_transaction() -> TestResult {
+    let cell = transaction(100)?;
+    let tx = Transaction::parse(&cell)?;
+    assert_eq!(tx.hash.0, cell.repr_hash());
+    assert_eq!((tx.account.0, tx.lt, tx.prev_trans_lt, tx.now), ([1; 32], 100, 90, 1700000000));
+    assert_eq!((tx.orig_status, tx.end_status), (AccountStatus::Active, AccountStatus::Active));
+    assert_eq!(tx.total_fees, CurrencyCollection { grams: 3641333, other: Vec::new() });
+
+    let in_msg = tx.in_msg.ok_or("missing inbound message")?;
+    let MsgInfo::ExternalIn { src, dest, import_fee } = in_msg.info else {
+        return Err("inbound message is not external".into());
+    };
+    assert_eq!((src, import_fee), (MsgAddress::None, 0));
+    assert_eq!(dest, MsgAddress::Std(AccountId { workchain: 0, id: Int256([1; 32]) }));
+    assert_eq!(tx.out_msgs.len(), 1);
+    let MsgInfo::Internal { bounce, dest, value, fwd_fee, created_lt, .. } = &tx.out_msgs[0].info else {
+        return Err("outbound message is not internal".into());
+    };
+    assert_eq!((*bounce, value.grams, *fwd_fee, *created_lt), (true, 1_000_000_000, 666672, 101));
+    assert_eq!(*dest, MsgAddress::Std(AccountId { workchain: -1, id: Int256([2; 32]) }));
+
+    let descr = &tx.description;
+    assert_eq!((descr.kind, descr.aborted, descr.destroyed), (TransactionKind::Ordinary, false, false));
+    assert_eq!(descr.storage.as_ref().ok_or("missing storage phase")?.storage_fees_collected, 25);
+    assert!(descr.credit.is_none() && descr.bounce.is_none());
+    let compute = descr.compute.as_ref().ok_or("missing compute phase")?;
+    assert_eq!(compute.exit_code(), Some(-14));
+    let ComputePhase::Vm(vm) = compute else {
+        return Err("compute phase is skipped".into());
+    };
+    assert_eq!((vm.success, vm.gas_fees, vm.gas_used, vm.gas_credit, vm.vm_steps), (false, 3308000, 3308, Some(10000), 68));
+    let action = descr.action.as_ref().ok_or("missing action phase")?;
+    assert_eq!((action.success, action.total_fwd_fees, action.msgs_created), (true, Some(333328), 1));
+    assert_eq!(action.tot_msg_size, StorageUsed { cells: 1, bits: 200 });
+
+    let ids = vec![block_id(1), block_id(2)];
+    let list = TransactionList { ids: ids.clone(), transactions: BocWriter::new(vec![transaction(200)?, transaction(100)?]).to_bytes() };
+    let transactions = Transaction::from_list(&list)?;
+    assert_eq!(transactions.len(), 2);
+    assert_eq!((&transactions[0].0, transactions[0].1.lt), (&ids[0], 200));
+    assert_eq!((&transactions[1].0, transactions[1].1.lt), (&ids[1], 100));
+    let list = TransactionList { ids: ids[..1].to_vec(), ..list };
+    assert!(Transaction::from_list(&list).is_err());
+    Ok(())
+}
+
+fn block_id(seqno: u32) -> BlockIdExt {
+    BlockIdExt { workchain: 0, shard: 1 << 63, seqno, root_hash: Int256([seqno as u8; 32]), file_hash: Int256([0; 32]) }
+}
+
+fn account_cell(state: impl Fn(&mut CellBuilder) -> Result<()>) -> Result<Cell> {
+    let mut builder = CellBuilder::new();
+    builder.store_bit(true)?;
+    std_address(&mut builder, 0, 7)?;
+    builder.store_uint(1, 3)?.store_uint(5, 8)?.store_uint(2, 3)?.store_uint(1500, 16)?.store_uint(0, 3)?;
+    builder.store_uint(1700000000, 32)?.store_bit(false)?.store_uint(4242, 64)?;
+    let extra = make_dict(&[(key_bits(&239u32.to_be_bytes(), 32), CellBuilder::new().store_uint(1, 5)?.store_uint(77, 8)?.build()?)], 32)?;
+    builder.store_coins(123456789)?.store_bit(true)?.store_reference(extra)?;
+    state(&mut builder)?;
+    builder.build()
+}
+
+#[test]
+fn test_account() -> TestResult {
+    let code = CellBuilder::new().store_bytes(b"code")?.build()?;
+    let data = CellBuilder::new().store_bytes(b"data")?.build()?;
+    let active = account_cell(|builder| {
+        builder.store_bit(true)?.store_uint(0, 2)?;
+        builder.store_bit(true)?.store_reference(code.clone())?.store_bit(true)?.store_reference(data.clone())?.store_bit(false)?;
+        Ok(())
+    })?;
+    let account = Account::parse(&active)?.ok_or("account does not exist")?;
+    assert_eq!(account.address, MsgAddress::Std(AccountId { workchain: 0, id: Int256([7; 32]) }));
+    assert_eq!((account.storage_used, &account.storage_dict_hash), (StorageUsed { cells: 5, bits: 1500 }, &None));
+    assert_eq!((account.last_paid, account.due_payment, account.last_trans_lt), (1700000000, None, 4242));
+    assert_eq!(account.balance, CurrencyCollection { grams: 123456789, other: vec![(239, 77)] });
+    assert_eq!(account.status(), AccountStatus::Active);
+    let state_init = account.state_init().ok_or("account is not active")?;
+    assert_eq!(state_init.code_hash(), Some(Int256(code.repr_hash())));
+    assert_eq!(state_init.data_hash(), Some(Int256(data.repr_hash())));
+
+    let frozen = Account::parse(&account_cell(|builder| {
+        builder.store_uint(0b01, 2)?.store_bytes(&[8; 32])?;
+        Ok(())
+    })?)?;
+    assert_eq!(frozen.ok_or("account does not exist")?.state, AccountStorageState::Frozen { state_hash: Int256([8; 32]) });
+    assert!(Account::parse(&CellBuilder::new().store_bit(false)?.build()?)?.is_none());
+    Ok(())
+}
+
+fn ext_blk_ref(seqno: u32) -> Result<Cell> {
+    CellBuilder::new().store_uint(seqno as u64 * 1000, 64)?.store_uint(seqno as u64, 32)?.store_bytes(&[seqno as u8; 32])?.store_bytes(&[0; 32])?.build()
+}
+
+fn currency_collection(builder: &mut CellBuilder, grams: u128) -> Result<()> {
+    builder.store_coins(grams)?.store_bit(false)?;
+    Ok(())
+}
+
+/// `HashmapAugE` of a single entry with `CurrencyCollection` extra
+fn aug_dict(key: &[u8], value: &mut CellBuilder) -> Result<Cell> {
+    let dict = make_dict(&[(key_bits(key, key.len() * 8), value.build()?)], key.len() * 8)?;
+    let mut builder = CellBuilder::new();
+    builder.store_bit(true)?.store_reference(dict)?;
+    currency_collection(&mut builder, 0)?;
+    builder.build()
+}
+
+/// Shard block after split with an external message processed by two transactions of one account
+fn block() -> Result<Cell> {
+    let info = CellBuilder::new()
+        .store_uint(0x9bc7a987, 32)?
+        .store_uint(0, 32)?
+        .store_uint(0b10010100, 8)?
+        .store_uint(1, 8)?
+        .store_uint(10, 32)?
+        .store_uint(0, 32)?
+        .store_uint(0, 2)?
+        .store_uint(1, 6)?
+        .store_int(0, 32)?
+        .store_uint(0x8000000000000000, 64)?
+        .store_uint(1700000000, 32)?
+        .store_uint(100, 64)?
+        .store_uint(201, 64)?
+        .store_uint(0, 32)?
+        .store_uint(5, 32)?
+        .store_uint(3, 32)?
+        .store_uint(2, 32)?
+        .store_uint(0xc4, 8)?
+        .store_uint(9, 32)?
+        .store_uint(0x2e, 64)?
+        .store_reference(ext_blk_ref(3)?)?
+        .store_reference(ext_blk_ref(9)?)?
+        .build()?;
+
+    let mut first = CellBuilder::new();
+    let mut second = CellBuilder::new();
+    for grams in [1, 2, 3, 4] {
+        currency_collection(&mut first, grams)?;
+        currency_collection(&mut second, grams + 10)?;
+    }
+    let mut value_flow = CellBuilder::new();
+    value_flow.store_uint(0x3ebf98b7, 32)?.store_reference(first.build()?)?;
+    currency_collection(&mut value_flow, 5)?;
+    currency_collection(&mut value_flow, 6)?;
+    value_flow.store_reference(second.build()?)?;
+
+    let tx = transaction(100)?;
+    let in_msg = tx.references()[0].references()[0].clone();
+    let mut in_msg_value = CellBuilder::new();
+    in_msg_value.store_coins(0)?;
+    currency_collection(&mut in_msg_value, 0)?;
+    in_msg_value.store_uint(0b000, 3)?.store_reference(in_msg.clone())?.store_reference(tx.clone())?;
+    let out_msg = CellBuilder::new().store_uint(0b0110, 4)?.build()?;
+    let envelope = CellBuilder::new().store_uint(4, 4)?.store_uint(0, 16)?.store_coins(0)?.store_reference(out_msg.clone())?.build()?;
+    let mut out_msg_value = CellBuilder::new();
+    currency_collection(&mut out_msg_value, 0)?;
+    out_msg_value.store_uint(0b001, 3)?.store_reference(envelope)?.store_reference(tx.clone())?;
+
+    let transactions = [100u64, 200]
+        .into_iter()
+        .map(|lt| {
+            let mut value = CellBuilder::new();
+            currency_collection(&mut value, 0)?;
+            Ok((key_bits(&lt.to_be_bytes(), 64), value.store_reference(transaction(lt)?)?.build()?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut account_block = CellBuilder::new();
+    currency_collection(&mut account_block, 0)?;
+    account_block.store_uint(5, 4)?.store_bytes(&[1; 32])?;
+    account_block.store_slice(&make_dict(&transactions, 64)?.slice())?.store_reference(CellBuilder::new().build()?)?;
+
+    let extra = CellBuilder::new()
+        .store_uint(0x4a33f6fd, 32)?
+        .store_reference(aug_dict(&in_msg.repr_hash(), &mut in_msg_value)?)?
+        .store_reference(aug_dict(&out_msg.repr_hash(), &mut out_msg_value)?)?
+        .store_reference(aug_dict(&[1; 32], &mut account_block)?)?
+        .store_bytes(&[7; 32])?
+        .store_bytes(&[8; 32])?
+        .store_bit(false)?
+        .build()?;
+    CellBuilder::new()
+        .store_uint(0x11ef55aa, 32)?
+        .store_int(-239, 32)?
+        .store_reference(info)?
+        .store_reference(value_flow.build()?)?
+        .store_reference(CellBuilder::new().build()?)?
+        .store_reference(extra)?
+        .build()
+}
+
+#[test]
+fn test_block() -> TestResult {
+    let cell = block()?;
+    let block = Block::from_boc(&BocWriter::new(vec![cell.clone()]).to_bytes())?;
+    assert_eq!((block.global_id, block.hash()), (-239, cell.repr_hash()));
+
+    let info = block.info()?;
+    assert_eq!((info.seqno, info.gen_utime, info.start_lt, info.end_lt), (10, 1700000000, 100, 201));
+    assert!(info.after_split && info.want_merge && !info.after_merge && !info.key_block && !info.is_masterchain());
+    assert_eq!(info.shard, ShardIdent::new(0, 0xc000000000000000).ok_or("invalid shard")?);
+    assert_eq!((info.gen_catchain_seqno, info.min_ref_mc_seqno, info.prev_key_block_seqno), (5, 3, 2));
+    assert_eq!(info.gen_software, Some(GlobalVersion { version: 9, capabilities: 0x2e }));
+    let master = info.master_block_id().ok_or("missing masterchain reference")?;
+    assert_eq!((master.workchain, master.shard, master.seqno), (-1, 1 << 63, 3));
+    let prev = info.prev_block_ids()?;
+    assert_eq!(prev.len(), 1);
+    assert_eq!((prev[0].shard, prev[0].seqno, prev[0].root_hash.0), (1 << 63, 9, [9; 32]));
+    assert_eq!(info.prev_refs[0].end_lt, 9000);
+
+    let value_flow = block.value_flow()?;
+    assert_eq!((value_flow.from_prev_blk.grams, value_flow.exported.grams, value_flow.fees_collected.grams), (1, 4, 5));
+    assert_eq!((value_flow.burned.grams, value_flow.fees_imported.grams, value_flow.minted.grams), (6, 11, 14));
+
+    let extra = block.extra()?;
+    assert_eq!((extra.rand_seed.0, extra.created_by.0), ([7; 32], [8; 32]));
+    assert!(extra.shard_hashes()?.is_none());
+    let in_msgs = extra.in_msgs()?;
+    assert_eq!(in_msgs.len(), 1);
+    assert_eq!((in_msgs[0].kind, in_msgs[0].hash.0), (InMsgKind::External, in_msgs[0].message.repr_hash()));
+    assert!(matches!(in_msgs[0].message()?.info, MsgInfo::ExternalIn { .. }));
+    assert_eq!(in_msgs[0].transaction()?.ok_or("missing transaction")?.lt, 100);
+    let out_msgs = extra.out_msgs()?;
+    assert_eq!(out_msgs.len(), 1);
+    assert_eq!(out_msgs[0].kind, OutMsgKind::New);
+    assert_eq!(out_msgs[0].message.as_ref().map(Cell::repr_hash), Some(out_msgs[0].hash.0));
+
+    let account_blocks = extra.account_blocks()?;
+    assert_eq!(account_blocks.len(), 1);
+    assert_eq!(account_blocks[0].account.0, [1; 32]);
+    let lts: Vec<_> = account_blocks[0].transaction_cells()?.into_iter().map(|(lt, _)| lt).collect();
+    assert_eq!(lts, [100, 200]);
+    let transactions = account_blocks[0].transactions()?;
+    assert_eq!((transactions[0].lt, transactions[1].lt), (100, 200));
+    Ok(())
+}
@@ -0,0 +1,421 @@
+use crate::boc::{deserialize, dict_entries, BocError, Cell, CellSlice};
+use crate::tl::common::{BlockIdExt, Int256};
+use crate::tl::response::TransactionList;
+
+use super::{load_int256, CurrencyCollection, Message, Result};
+
+const TRANSACTION_TAG: u64 = 0b0111;
+
+/// Status of account, also before and after transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountStatus {
+    Uninit,
+    Frozen,
+    Active,
+    NonExist,
+}
+
+impl AccountStatus {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        Ok(match slice.load_uint(2)? {
+            0b00 => Self::Uninit,
+            0b01 => Self::Frozen,
+            0b10 => Self::Active,
+            _ => Self::NonExist,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccStatusChange {
+    Unchanged,
+    Frozen,
+    Deleted,
+}
+
+impl AccStatusChange {
+    fn parse(slice: &mut CellSlice) -> Result<Self> {
+        if !slice.load_bit()? {
+            return Ok(Self::Unchanged);
+        }
+        Ok(if slice.load_bit()? { Self::Deleted } else { Self::Frozen })
+    }
+}
+
+/// Number of cells and bits, `StorageUsedShort`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageUsed {
+    pub cells: u64,
+    pub bits: u64,
+}
+
+impl StorageUsed {
+    pub(super) fn parse(slice: &mut CellSlice) -> Result<Self> {
+        Ok(Self { cells: slice.load_var_uint(3)? as u64, bits: slice.load_var_uint(3)? as u64 })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoragePhase {
+    pub storage_fees_collected: u128,
+    pub storage_fees_due: Option<u128>,
+    pub status_change: AccStatusChange,
+}
+
+impl StoragePhase {
+    fn parse(slice: &mut CellSlice) -> Result<Self> {
+        Ok(Self {
+            storage_fees_collected: slice.load_coins()?,
+            storage_fees_due: load_maybe_coins(slice)?,
+            status_change: AccStatusChange::parse(slice)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreditPhase {
+    pub due_fees_collected: Option<u128>,
+    pub credit: CurrencyCollection,
+}
+
+impl CreditPhase {
+    fn parse(slice: &mut CellSlice) -> Result<Self> {
+        Ok(Self { due_fees_collected: load_maybe_coins(slice)?, credit: CurrencyCollection::parse(slice)? })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeSkipReason {
+    NoState,
+    BadState,
+    NoGas,
+    Suspended,
+}
+
+/// Compute phase executed by TVM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputeVm {
+    pub success: bool,
+    pub msg_state_used: bool,
+    pub account_activated: bool,
+    pub gas_fees: u128,
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    pub gas_credit: Option<u64>,
+    pub mode: i8,
+    pub exit_code: i32,
+    pub exit_arg: Option<i32>,
+    pub vm_steps: u32,
+    pub vm_init_state_hash: Int256,
+    pub vm_final_state_hash: Int256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputePhase {
+    Skipped(ComputeSkipReason),
+    Vm(ComputeVm),
+}
+
+impl ComputePhase {
+    fn parse(slice: &mut CellSlice) -> Result<Self> {
+        if !slice.load_bit()? {
+            let reason = match slice.load_uint(2)? {
+                0b00 => ComputeSkipReason::NoState,
+                0b01 => ComputeSkipReason::BadState,
+                0b10 => ComputeSkipReason::NoGas,
+                _ if !slice.load_bit()? => ComputeSkipReason::Suspended,
+                _ => return Err(BocError::InvalidCell("invalid compute skip reason")),
+            };
+            return Ok(Self::Skipped(reason));
+        }
+        let success = slice.load_bit()?;
+        let msg_state_used = slice.load_bit()?;
+        let account_activated = slice.load_bit()?;
+        let gas_fees = slice.load_coins()?;
+        let mut details = slice.load_reference()?.parse()?;
+        Ok(Self::Vm(ComputeVm {
+            success,
+            msg_state_used,
+            account_activated,
+            gas_fees,
+            gas_used: details.load_var_uint(3)? as u64,
+            gas_limit: details.load_var_uint(3)? as u64,
+            gas_credit: if details.load_bit()? { Some(details.load_var_uint(2)? as u64) } else { None },
+            mode: details.load_int(8)? as i8,
+            exit_code: details.load_int(32)? as i32,
+            exit_arg: if details.load_bit()? { Some(details.load_int(32)? as i32) } else { None },
+            vm_steps: details.load_uint(32)? as u32,
+            vm_init_state_hash: load_int256(&mut details)?,
+            vm_final_state_hash: load_int256(&mut details)?,
+        }))
+    }
+
+    /// Exit code of TVM, `None` if the phase was skipped
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            Self::Skipped(_) => None,
+            Self::Vm(vm) => Some(vm.exit_code),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionPhase {
+    pub success: bool,
+    pub valid: bool,
+    pub no_funds: bool,
+    pub status_change: AccStatusChange,
+    pub total_fwd_fees: Option<u128>,
+    pub total_action_fees: Option<u128>,
+    pub result_code: i32,
+    pub result_arg: Option<i32>,
+    pub tot_actions: u16,
+    pub spec_actions: u16,
+    pub skipped_actions: u16,
+    pub msgs_created: u16,
+    pub action_list_hash: Int256,
+    pub tot_msg_size: StorageUsed,
+}
+
+impl ActionPhase {
+    fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        Ok(Self {
+            success: slice.load_bit()?,
+            valid: slice.load_bit()?,
+            no_funds: slice.load_bit()?,
+            status_change: AccStatusChange::parse(&mut slice)?,
+            total_fwd_fees: load_maybe_coins(&mut slice)?,
+            total_action_fees: load_maybe_coins(&mut slice)?,
+            result_code: slice.load_int(32)? as i32,
+            result_arg: if slice.load_bit()? { Some(slice.load_int(32)? as i32) } else { None },
+            tot_actions: slice.load_uint(16)? as u16,
+            spec_actions: slice.load_uint(16)? as u16,
+            skipped_actions: slice.load_uint(16)? as u16,
+            msgs_created: slice.load_uint(16)? as u16,
+            action_list_hash: load_int256(&mut slice)?,
+            tot_msg_size: StorageUsed::parse(&mut slice)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BouncePhase {
+    NegativeFunds,
+    NoFunds { msg_size: StorageUsed, req_fwd_fees: u128 },
+    Ok { msg_size: StorageUsed, msg_fees: u128, fwd_fees: u128 },
+}
+
+impl BouncePhase {
+    fn parse(slice: &mut CellSlice) -> Result<Self> {
+        if slice.load_bit()? {
+            return Ok(Self::Ok { msg_size: StorageUsed::parse(slice)?, msg_fees: slice.load_coins()?, fwd_fees: slice.load_coins()? });
+        }
+        if slice.load_bit()? {
+            return Ok(Self::NoFunds { msg_size: StorageUsed::parse(slice)?, req_fwd_fees: slice.load_coins()? });
+        }
+        Ok(Self::NegativeFunds)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionKind {
+    Ordinary,
+    Storage,
+    Tick,
+    Tock,
+    SplitPrepare,
+    SplitInstall,
+    MergePrepare,
+    MergeInstall,
+}
+
+/// Phases of transaction, phases missing in its kind of transaction are `None`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionDescr {
+    pub kind: TransactionKind,
+    pub credit_first: bool,
+    pub storage: Option<StoragePhase>,
+    pub credit: Option<CreditPhase>,
+    pub compute: Option<ComputePhase>,
+    pub action: Option<ActionPhase>,
+    pub bounce: Option<BouncePhase>,
+    pub aborted: bool,
+    pub destroyed: bool,
+}
+
+impl TransactionDescr {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        let slice = &mut slice;
+        let mut descr = Self {
+            kind: TransactionKind::Ordinary,
+            credit_first: false,
+            storage: None,
+            credit: None,
+            compute: None,
+            action: None,
+            bounce: None,
+            aborted: false,
+            destroyed: false,
+        };
+        match slice.load_uint(3)? {
+            0b000 => {
+                if slice.load_bit()? {
+                    descr.kind = TransactionKind::Storage;
+                    descr.storage = Some(StoragePhase::parse(slice)?);
+                    return Ok(descr);
+                }
+                // trans_ord$0000
+                descr.credit_first = slice.load_bit()?;
+                descr.storage = load_maybe(slice, StoragePhase::parse)?;
+                descr.credit = load_maybe(slice, CreditPhase::parse)?;
+                descr.compute = Some(ComputePhase::parse(slice)?);
+                descr.action = load_maybe_action(slice)?;
+                descr.aborted = slice.load_bit()?;
+                descr.bounce = load_maybe(slice, BouncePhase::parse)?;
+                descr.destroyed = slice.load_bit()?;
+            }
+            0b001 => {
+                descr.kind = if slice.load_bit()? { TransactionKind::Tock } else { TransactionKind::Tick };
+                descr.storage = Some(StoragePhase::parse(slice)?);
+                descr.compute = Some(ComputePhase::parse(slice)?);
+                descr.action = load_maybe_action(slice)?;
+                descr.aborted = slice.load_bit()?;
+                descr.destroyed = slice.load_bit()?;
+            }
+            0b010 => {
+                let install = slice.load_bit()?;
+                skip_split_merge_info(slice)?;
+                if install {
+                    // prepare_transaction:^Transaction installed:Bool
+                    descr.kind = TransactionKind::SplitInstall;
+                    slice.load_reference()?;
+                    slice.load_bit()?;
+                    return Ok(descr);
+                }
+                descr.kind = TransactionKind::SplitPrepare;
+                descr.storage = load_maybe(slice, StoragePhase::parse)?;
+                descr.compute = Some(ComputePhase::parse(slice)?);
+                descr.action = load_maybe_action(slice)?;
+                descr.aborted = slice.load_bit()?;
+                descr.destroyed = slice.load_bit()?;
+            }
+            0b011 => {
+                let install = slice.load_bit()?;
+                skip_split_merge_info(slice)?;
+                if !install {
+                    descr.kind = TransactionKind::MergePrepare;
+                    descr.storage = Some(StoragePhase::parse(slice)?);
+                    descr.aborted = slice.load_bit()?;
+                    return Ok(descr);
+                }
+                descr.kind = TransactionKind::MergeInstall;
+                slice.load_reference()?;
+                descr.storage = load_maybe(slice, StoragePhase::parse)?;
+                descr.credit = load_maybe(slice, CreditPhase::parse)?;
+                descr.compute = Some(ComputePhase::parse(slice)?);
+                descr.action = load_maybe_action(slice)?;
+                descr.aborted = slice.load_bit()?;
+                descr.destroyed = slice.load_bit()?;
+            }
+            _ => return Err(BocError::InvalidCell("invalid transaction description tag")),
+        }
+        Ok(descr)
+    }
+}
+
+/// Transaction of account, `Transaction`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    /// Hash of the transaction cell
+    pub hash: Int256,
+    /// Account address within its workchain
+    pub account: Int256,
+    pub lt: u64,
+    pub prev_trans_hash: Int256,
+    pub prev_trans_lt: u64,
+    pub now: u32,
+    pub orig_status: AccountStatus,
+    pub end_status: AccountStatus,
+    pub in_msg: Option<Message>,
+    /// Outbound messages in the order they were created
+    pub out_msgs: Vec<Message>,
+    pub total_fees: CurrencyCollection,
+    pub description: TransactionDescr,
+}
+
+impl Transaction {
+    pub fn parse(cell: &Cell) -> Result<Self> {
+        let mut slice = cell.parse()?;
+        if slice.load_uint(4)? != TRANSACTION_TAG {
+            return Err(BocError::InvalidCell("invalid transaction tag"));
+        }
+        let account = load_int256(&mut slice)?;
+        let lt = slice.load_uint(64)?;
+        let prev_trans_hash = load_int256(&mut slice)?;
+        let prev_trans_lt = slice.load_uint(64)?;
+        let now = slice.load_uint(32)? as u32;
+        // outmsg_cnt
+        slice.skip_bits(15)?;
+        let orig_status = AccountStatus::parse(&mut slice)?;
+        let end_status = AccountStatus::parse(&mut slice)?;
+        // ^[ in_msg:(Maybe ^(Message Any)) out_msgs:(HashmapE 15 ^(Message Any)) ]
+        let mut messages = slice.load_reference()?.parse()?;
+        let in_msg = if messages.load_bit()? { Some(Message::parse(&messages.load_reference()?)?) } else { None };
+        let out_msgs = if messages.load_bit()? {
+            dict_entries(&messages.load_reference()?, 15)?
+                .into_iter()
+                .map(|(_, mut value)| Message::parse(&value.load_reference()?))
+                .collect::<Result<_>>()?
+        } else {
+            Vec::new()
+        };
+        let total_fees = CurrencyCollection::parse(&mut slice)?;
+        // state_update
+        slice.load_reference()?;
+        let description = TransactionDescr::parse(&slice.load_reference()?)?;
+        Ok(Self {
+            hash: Int256(cell.repr_hash()),
+            account,
+            lt,
+            prev_trans_hash,
+            prev_trans_lt,
+            now,
+            orig_status,
+            end_status,
+            in_msg,
+            out_msgs,
+            total_fees,
+            description,
+        })
+    }
+
+    /// Decodes transactions of `liteServer.transactionList` with the ids of their blocks
+    pub fn from_list(list: &TransactionList) -> Result<Vec<(BlockIdExt, Self)>> {
+        let roots = if list.transactions.is_empty() { Vec::new() } else { deserialize(&list.transactions)? };
+        if roots.len() != list.ids.len() {
+            return Err(BocError::InvalidHeader("number of transactions does not match number of block ids"));
+        }
+        list.ids.iter().zip(&roots).map(|(id, root)| Ok((id.clone(), Self::parse(root)?))).collect()
+    }
+}
+
+fn load_maybe<T>(slice: &mut CellSlice, parse: fn(&mut CellSlice) -> Result<T>) -> Result<Option<T>> {
+    Ok(if slice.load_bit()? { Some(parse(slice)?) } else { None })
+}
+
+fn load_maybe_coins(slice: &mut CellSlice) -> Result<Option<u128>> {
+    load_maybe(slice, CellSlice::load_coins)
+}
+
+/// Loads `action:(Maybe ^TrActionPhase)`
+fn load_maybe_action(slice: &mut CellSlice) -> Result<Option<ActionPhase>> {
+    Ok(if slice.load_bit()? { Some(ActionPhase::parse(&slice.load_reference()?)?) } else { None })
+}
+
+/// Skips `split_merge_info$_ cur_shard_pfx_len:(## 6) acc_split_depth:(## 6) this_addr:bits256
+/// sibling_addr:bits256`
+fn skip_split_merge_info(slice: &mut CellSlice) -> Result<()> {
+    slice.skip_bits(6 + 6 + 256 + 256)
+}
@@ -0,0 +1,128 @@
+use super::cell::{Cell, CellType, MAX_BITS, MAX_REFS};
+use super::slice::CellSlice;
+use super::BocError;
+
+type Result<T> = std::result::Result<T, BocError>;
+
+/// Accumulates bits and references of a new cell
+#[derive(Debug, Clone, Default)]
+pub struct CellBuilder {
+    data: Vec<u8>,
+    bit_len: usize,
+    references: Vec<Cell>,
+}
+
+impl CellBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bit_len(&self) -> usize {
+        self.bit_len
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        MAX_BITS - self.bit_len
+    }
+
+    pub fn remaining_refs(&self) -> usize {
+        MAX_REFS - self.references.len()
+    }
+
+    pub fn store_bit(&mut self, bit: bool) -> Result<&mut Self> {
+        if self.bit_len == MAX_BITS {
+            return Err(BocError::CellOverflow);
+        }
+        if self.bit_len.is_multiple_of(8) {
+            self.data.push(0);
+        }
+        if bit {
+            self.data[self.bit_len / 8] |= 0x80 >> (self.bit_len % 8);
+        }
+        self.bit_len += 1;
+        Ok(self)
+    }
+
+    /// Stores first `bit_len` bits of `data`
+    pub fn store_bits(&mut self, data: &[u8], bit_len: usize) -> Result<&mut Self> {
+        if bit_len > data.len() * 8 {
+            return Err(BocError::CellUnderflow);
+        }
+        if bit_len > self.remaining_bits() {
+            return Err(BocError::CellOverflow);
+        }
+        for i in 0..bit_len {
+            self.store_bit(data[i / 8] & (0x80 >> (i % 8)) != 0)?;
+        }
+        Ok(self)
+    }
+
+    pub fn store_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self> {
+        self.store_bits(bytes, bytes.len() * 8)
+    }
+
+    /// Stores `bits` lowest bits of unsigned `value`, big-endian
+    pub fn store_uint(&mut self, value: u64, bits: usize) -> Result<&mut Self> {
+        if bits > 64 || (bits < 64 && value >> bits != 0) {
+            return Err(BocError::IntegerOverflow);
+        }
+        if bits == 0 {
+            return Ok(self);
+        }
+        self.store_bits(&(value << (64 - bits)).to_be_bytes(), bits)
+    }
+
+    /// Stores signed `value` in two's complement using `bits` bits
+    pub fn store_int(&mut self, value: i64, bits: usize) -> Result<&mut Self> {
+        if bits == 0 || bits > 64 {
+            return Err(BocError::IntegerOverflow);
+        }
+        if bits < 64 && (value >> (bits - 1) != 0 && value >> (bits - 1) != -1) {
+            return Err(BocError::IntegerOverflow);
+        }
+        let value = if bits < 64 { value as u64 & ((1 << bits) - 1) } else { value as u64 };
+        self.store_uint(value, bits)
+    }
+
+    /// Stores amount of nanotons as `VarUInteger 16`
+    pub fn store_coins(&mut self, amount: u128) -> Result<&mut Self> {
+        let len = 16 - amount.leading_zeros() as usize / 8;
+        if len > 15 {
+            return Err(BocError::IntegerOverflow);
+        }
+        self.store_uint(len as u64, 4)?;
+        self.store_bits(&amount.to_be_bytes()[16 - len..], len * 8)
+    }
+
+    pub fn store_reference(&mut self, cell: Cell) -> Result<&mut Self> {
+        if self.references.len() == MAX_REFS {
+            return Err(BocError::CellOverflow);
+        }
+        self.references.push(cell);
+        Ok(self)
+    }
+
+    /// Stores remaining bits and references of the slice
+    pub fn store_slice(&mut self, slice: &CellSlice) -> Result<&mut Self> {
+        if slice.remaining_bits() > self.remaining_bits() || slice.remaining_refs() > self.remaining_refs() {
+            return Err(BocError::CellOverflow);
+        }
+        let mut slice = slice.clone();
+        while slice.remaining_bits() > 0 {
+            self.store_bit(slice.load_bit()?)?;
+        }
+        while slice.remaining_refs() > 0 {
+            self.store_reference(slice.load_reference()?)?;
+        }
+        Ok(self)
+    }
+
+    pub fn build(&self) -> Result<Cell> {
+        Cell::new(CellType::Ordinary, self.data.clone(), self.bit_len, self.references.clone())
+    }
+
+    /// Builds an exotic cell, the data has to start with the tag of `cell_type`
+    pub fn build_exotic(&self, cell_type: CellType) -> Result<Cell> {
+        Cell::new(cell_type, self.data.clone(), self.bit_len, self.references.clone())
+    }
+}
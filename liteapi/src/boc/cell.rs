@@ -0,0 +1,367 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+use super::BocError;
+
+type Result<T> = std::result::Result<T, BocError>;
+
+pub const MAX_BITS: usize = 1023;
+pub const MAX_REFS: usize = 4;
+pub const MAX_LEVEL: u8 = 3;
+pub const MAX_DEPTH: u16 = 1024;
+
+const HASH_BITS: usize = 256;
+const DEPTH_BITS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellType {
+    Ordinary,
+    PrunedBranch,
+    LibraryReference,
+    MerkleProof,
+    MerkleUpdate,
+}
+
+impl CellType {
+    /// Type of the exotic cell by the first byte of its data
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(CellType::PrunedBranch),
+            2 => Some(CellType::LibraryReference),
+            3 => Some(CellType::MerkleProof),
+            4 => Some(CellType::MerkleUpdate),
+            _ => None,
+        }
+    }
+
+    pub fn tag(&self) -> Option<u8> {
+        match self {
+            CellType::Ordinary => None,
+            CellType::PrunedBranch => Some(1),
+            CellType::LibraryReference => Some(2),
+            CellType::MerkleProof => Some(3),
+            CellType::MerkleUpdate => Some(4),
+        }
+    }
+
+    pub fn is_exotic(&self) -> bool {
+        *self != CellType::Ordinary
+    }
+
+    pub fn is_merkle(&self) -> bool {
+        matches!(self, CellType::MerkleProof | CellType::MerkleUpdate)
+    }
+}
+
+/// Set of levels at which hashes of the cell differ, one bit per level starting from level 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LevelMask(u8);
+
+impl LevelMask {
+    pub fn new(mask: u8) -> Self {
+        Self(mask & 7)
+    }
+
+    pub fn mask(&self) -> u8 {
+        self.0
+    }
+
+    pub fn level(&self) -> u8 {
+        8 - self.0.leading_zeros() as u8
+    }
+
+    /// Index of the hash used at given level
+    pub fn hash_index(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn hashes_count(&self) -> usize {
+        self.hash_index() + 1
+    }
+
+    /// Mask as seen from given level
+    pub fn apply(&self, level: u8) -> Self {
+        Self(self.0 & ((1u8 << level.min(7)) - 1))
+    }
+
+    pub fn is_significant(&self, level: u8) -> bool {
+        level == 0 || (self.0 >> (level - 1)) & 1 != 0
+    }
+
+    pub fn shift_right(&self) -> Self {
+        Self(self.0 >> 1)
+    }
+}
+
+impl std::ops::BitOr for LevelMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+struct CellInner {
+    cell_type: CellType,
+    data: Vec<u8>,
+    bit_len: usize,
+    references: Vec<Cell>,
+    level_mask: LevelMask,
+    hashes: Vec<[u8; 32]>,
+    depths: Vec<u16>,
+}
+
+/// Node of the cell DAG, cheap to clone.
+///
+/// Hashes and depths of all levels are computed once the cell is created.
+#[derive(Clone)]
+pub struct Cell(Arc<CellInner>);
+
+impl Cell {
+    /// Creates a cell from its data, checking the layout of exotic cells.
+    ///
+    /// Bits of `data` after `bit_len` are ignored.
+    pub fn new(cell_type: CellType, mut data: Vec<u8>, bit_len: usize, references: Vec<Cell>) -> Result<Self> {
+        if bit_len > MAX_BITS {
+            return Err(BocError::InvalidCell("too many bits"));
+        }
+        if references.len() > MAX_REFS {
+            return Err(BocError::InvalidCell("too many references"));
+        }
+        let byte_len = bit_len.div_ceil(8);
+        if data.len() < byte_len {
+            return Err(BocError::InvalidCell("not enough data"));
+        }
+        data.truncate(byte_len);
+        if !bit_len.is_multiple_of(8) {
+            data[byte_len - 1] &= 0xff << (8 - bit_len % 8);
+        }
+        let level_mask = Self::compute_level_mask(cell_type, &data, bit_len, &references)?;
+        let mut inner = CellInner {
+            cell_type,
+            data,
+            bit_len,
+            references,
+            level_mask,
+            hashes: Vec::new(),
+            depths: Vec::new(),
+        };
+        Self::compute_hashes(&mut inner)?;
+        Ok(Self(Arc::new(inner)))
+    }
+
+    pub fn ordinary(data: Vec<u8>, bit_len: usize, references: Vec<Cell>) -> Result<Self> {
+        Self::new(CellType::Ordinary, data, bit_len, references)
+    }
+
+    fn compute_level_mask(cell_type: CellType, data: &[u8], bit_len: usize, references: &[Cell]) -> Result<LevelMask> {
+        if cell_type.is_exotic() && (bit_len < 8 || Some(data[0]) != cell_type.tag()) {
+            return Err(BocError::InvalidCell("exotic cell type mismatch"));
+        }
+        let children_mask = references.iter().fold(LevelMask::default(), |mask, cell| mask | cell.level_mask());
+        match cell_type {
+            CellType::Ordinary => Ok(children_mask),
+            CellType::PrunedBranch => {
+                if !references.is_empty() {
+                    return Err(BocError::InvalidCell("pruned branch with references"));
+                }
+                if bit_len < 16 {
+                    return Err(BocError::InvalidCell("pruned branch without level mask"));
+                }
+                let mask = LevelMask::new(data[1]);
+                if mask.level() == 0 || mask.mask() != data[1] {
+                    return Err(BocError::InvalidCell("invalid pruned branch level mask"));
+                }
+                if bit_len != 16 + mask.hash_index() * (HASH_BITS + DEPTH_BITS) {
+                    return Err(BocError::InvalidCell("invalid pruned branch length"));
+                }
+                Ok(mask)
+            }
+            CellType::LibraryReference => {
+                if !references.is_empty() || bit_len != 8 + HASH_BITS {
+                    return Err(BocError::InvalidCell("invalid library reference"));
+                }
+                Ok(LevelMask::default())
+            }
+            CellType::MerkleProof | CellType::MerkleUpdate => {
+                let children = if cell_type == CellType::MerkleProof { 1 } else { 2 };
+                if references.len() != children || bit_len != 8 + children * (HASH_BITS + DEPTH_BITS) {
+                    return Err(BocError::InvalidCell("invalid merkle cell"));
+                }
+                for (i, child) in references.iter().enumerate() {
+                    let hash_offset = 1 + i * 32;
+                    let depth_offset = 1 + children * 32 + i * 2;
+                    let depth = u16::from_be_bytes([data[depth_offset], data[depth_offset + 1]]);
+                    if data[hash_offset..hash_offset + 32] != child.hash(0) || depth != child.depth(0) {
+                        return Err(BocError::InvalidCell("merkle cell does not match its child"));
+                    }
+                }
+                Ok(children_mask.shift_right())
+            }
+        }
+    }
+
+    fn compute_hashes(inner: &mut CellInner) -> Result<()> {
+        let mask = inner.level_mask;
+        // pruned branch keeps the hashes of lower levels in its data
+        let stored = if inner.cell_type == CellType::PrunedBranch { mask.hash_index() } else { 0 };
+        let level_offset = if inner.cell_type.is_merkle() { 1 } else { 0 };
+        for level in 0..=mask.level() {
+            if !mask.is_significant(level) || mask.apply(level).hash_index() < stored {
+                continue;
+            }
+            let mut hasher = Sha256::new();
+            let d1 = descriptor_d1(inner.references.len(), inner.cell_type.is_exotic(), mask.apply(level));
+            hasher.update([d1, descriptor_d2(inner.bit_len)]);
+            // higher level hashes are computed over the previous one instead of the data
+            match inner.hashes.last() {
+                None => hasher.update(data_with_tag(&inner.data, inner.bit_len)),
+                Some(hash) => hasher.update(hash),
+            }
+            let mut depth = 0;
+            for child in &inner.references {
+                let child_depth = child.depth(level + level_offset);
+                hasher.update(child_depth.to_be_bytes());
+                depth = depth.max(child_depth + 1);
+            }
+            if depth > MAX_DEPTH {
+                return Err(BocError::InvalidCell("cell is too deep"));
+            }
+            for child in &inner.references {
+                hasher.update(child.hash(level + level_offset));
+            }
+            inner.hashes.push(hasher.finalize().into());
+            inner.depths.push(depth);
+        }
+        Ok(())
+    }
+
+    pub fn cell_type(&self) -> CellType {
+        self.0.cell_type
+    }
+
+    pub fn is_exotic(&self) -> bool {
+        self.0.cell_type.is_exotic()
+    }
+
+    /// Data bits of the cell packed into bytes, unused bits of the last byte are zero
+    pub fn data(&self) -> &[u8] {
+        &self.0.data
+    }
+
+    pub fn bit_len(&self) -> usize {
+        self.0.bit_len
+    }
+
+    pub fn references(&self) -> &[Cell] {
+        &self.0.references
+    }
+
+    pub fn reference(&self, index: usize) -> Option<&Cell> {
+        self.0.references.get(index)
+    }
+
+    pub fn level_mask(&self) -> LevelMask {
+        self.0.level_mask
+    }
+
+    pub fn level(&self) -> u8 {
+        self.0.level_mask.level()
+    }
+
+    /// Hash of the cell at given level, where level 0 is the hash with all pruned branches
+    /// replaced by the original cells
+    pub fn hash(&self, level: u8) -> [u8; 32] {
+        let hash_index = self.0.level_mask.apply(level).hash_index();
+        if self.0.cell_type == CellType::PrunedBranch {
+            let stored = self.0.level_mask.hash_index();
+            if hash_index < stored {
+                let offset = 2 + hash_index * 32;
+                return self.0.data[offset..offset + 32].try_into().unwrap();
+            }
+            return self.0.hashes[hash_index - stored];
+        }
+        self.0.hashes[hash_index]
+    }
+
+    pub fn depth(&self, level: u8) -> u16 {
+        let hash_index = self.0.level_mask.apply(level).hash_index();
+        if self.0.cell_type == CellType::PrunedBranch {
+            let stored = self.0.level_mask.hash_index();
+            if hash_index < stored {
+                let offset = 2 + stored * 32 + hash_index * 2;
+                return u16::from_be_bytes([self.0.data[offset], self.0.data[offset + 1]]);
+            }
+            return self.0.depths[hash_index - stored];
+        }
+        self.0.depths[hash_index]
+    }
+
+    /// Representation hash, identifying the cell as it is serialized
+    pub fn repr_hash(&self) -> [u8; 32] {
+        self.hash(MAX_LEVEL)
+    }
+
+    pub fn repr_depth(&self) -> u16 {
+        self.depth(MAX_LEVEL)
+    }
+
+    /// Cell data with the completion tag, as it is stored in BOC
+    pub(crate) fn serialized_data(&self) -> Vec<u8> {
+        data_with_tag(&self.0.data, self.0.bit_len)
+    }
+
+    pub(crate) fn descriptors(&self) -> [u8; 2] {
+        [
+            descriptor_d1(self.0.references.len(), self.is_exotic(), self.0.level_mask),
+            descriptor_d2(self.0.bit_len),
+        ]
+    }
+}
+
+fn descriptor_d1(references: usize, exotic: bool, level_mask: LevelMask) -> u8 {
+    references as u8 + if exotic { 8 } else { 0 } + level_mask.mask() * 32
+}
+
+fn descriptor_d2(bit_len: usize) -> u8 {
+    (bit_len / 8 + bit_len.div_ceil(8)) as u8
+}
+
+fn data_with_tag(data: &[u8], bit_len: usize) -> Vec<u8> {
+    let mut data = data.to_vec();
+    if !bit_len.is_multiple_of(8) {
+        data[bit_len / 8] |= 0x80 >> (bit_len % 8);
+    }
+    data
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.repr_hash() == other.repr_hash()
+    }
+}
+
+impl Eq for Cell {}
+
+impl Hash for Cell {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.repr_hash().hash(state)
+    }
+}
+
+impl fmt::Debug for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Cell");
+        if self.is_exotic() {
+            debug.field("type", &self.cell_type());
+        }
+        debug
+            .field("bits", &self.bit_len())
+            .field("data", &format_args!("0x{}", hex::encode(self.data())))
+            .field("references", &self.references())
+            .finish()
+    }
+}
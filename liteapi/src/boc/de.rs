@@ -0,0 +1,173 @@
+use super::cell::{Cell, CellType};
+use super::{BocError, BOC_GENERIC_MAGIC, BOC_INDEXED_CRC32C_MAGIC, BOC_INDEXED_MAGIC, CRC32C};
+
+type Result<T> = std::result::Result<T, BocError>;
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.offset.checked_add(len).ok_or(BocError::UnexpectedEof)?;
+        let bytes = self.data.get(self.offset..end).ok_or(BocError::UnexpectedEof)?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_uint(&mut self, len: usize) -> Result<usize> {
+        let value = self.read_bytes(len)?.iter().fold(0u64, |acc, &b| acc << 8 | b as u64);
+        usize::try_from(value).map_err(|_| BocError::InvalidHeader("number is too large"))
+    }
+}
+
+struct RawCell<'a> {
+    cell_type: CellType,
+    data: &'a [u8],
+    bit_len: usize,
+    level_mask: u8,
+    references: Vec<usize>,
+}
+
+/// Parses all root cells of serialized bag of cells
+pub fn deserialize(data: &[u8]) -> Result<Vec<Cell>> {
+    let mut reader = Reader { data, offset: 0 };
+    let magic = u32::from_be_bytes(reader.read_bytes(4)?.try_into().unwrap());
+    let flags = reader.read_u8()?;
+    let (has_index, has_crc32c, has_cache_bits) = match magic {
+        BOC_GENERIC_MAGIC => (flags & 0x80 != 0, flags & 0x40 != 0, flags & 0x20 != 0),
+        BOC_INDEXED_MAGIC => (true, false, false),
+        BOC_INDEXED_CRC32C_MAGIC => (true, true, false),
+        magic => return Err(BocError::UnknownMagic(magic)),
+    };
+    let ref_size = (flags & 7) as usize;
+    if ref_size == 0 || ref_size > 4 {
+        return Err(BocError::InvalidHeader("invalid reference size"));
+    }
+    let offset_size = reader.read_u8()? as usize;
+    if offset_size == 0 || offset_size > 8 {
+        return Err(BocError::InvalidHeader("invalid offset size"));
+    }
+    let cells_count = reader.read_uint(ref_size)?;
+    let roots_count = reader.read_uint(ref_size)?;
+    let absent_count = reader.read_uint(ref_size)?;
+    let cells_size = reader.read_uint(offset_size)?;
+    if roots_count == 0 || roots_count > cells_count || absent_count > cells_count {
+        return Err(BocError::InvalidHeader("invalid number of cells"));
+    }
+    if has_cache_bits && !has_index {
+        return Err(BocError::InvalidHeader("cache bits without index"));
+    }
+
+    if has_crc32c {
+        if data.len() < 4 {
+            return Err(BocError::UnexpectedEof);
+        }
+        let (payload, checksum) = data.split_at(data.len() - 4);
+        if CRC32C.checksum(payload) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+            return Err(BocError::ChecksumMismatch);
+        }
+    }
+
+    let roots = if magic == BOC_GENERIC_MAGIC {
+        (0..roots_count).map(|_| reader.read_uint(ref_size)).collect::<Result<Vec<_>>>()?
+    } else if roots_count == 1 {
+        vec![0]
+    } else {
+        return Err(BocError::InvalidHeader("legacy format supports a single root"));
+    };
+    if roots.iter().any(|&root| root >= cells_count) {
+        return Err(BocError::InvalidHeader("root index out of range"));
+    }
+    if has_index {
+        // cells are read sequentially, so the index is not needed
+        reader.read_bytes(cells_count.checked_mul(offset_size).ok_or(BocError::UnexpectedEof)?)?;
+    }
+
+    let cells_data = reader.read_bytes(cells_size)?;
+    let mut cells_reader = Reader { data: cells_data, offset: 0 };
+    // each cell takes at least two bytes, which bounds the allocation below
+    if cells_count > cells_data.len() / 2 {
+        return Err(BocError::InvalidHeader("number of cells exceeds data size"));
+    }
+    let mut raw_cells = Vec::with_capacity(cells_count);
+    for index in 0..cells_count {
+        raw_cells.push(read_cell(&mut cells_reader, ref_size, index, cells_count)?);
+    }
+    if cells_reader.offset != cells_data.len() {
+        return Err(BocError::InvalidHeader("unused data after cells"));
+    }
+    let expected_len = reader.offset + if has_crc32c { 4 } else { 0 };
+    if expected_len != data.len() {
+        return Err(BocError::InvalidHeader("unused data after bag of cells"));
+    }
+
+    // references always point forward, so the cells are built from the last one
+    let mut cells: Vec<Option<Cell>> = vec![None; cells_count];
+    for (index, raw) in raw_cells.into_iter().enumerate().rev() {
+        let references = raw.references.iter().map(|&i| cells[i].clone().unwrap()).collect();
+        let cell = Cell::new(raw.cell_type, raw.data.to_vec(), raw.bit_len, references)?;
+        if cell.level_mask().mask() != raw.level_mask {
+            return Err(BocError::InvalidCell("level mask mismatch"));
+        }
+        cells[index] = Some(cell);
+    }
+    Ok(roots.into_iter().map(|root| cells[root].clone().unwrap()).collect())
+}
+
+impl Cell {
+    /// Parses serialized bag of cells with a single root
+    pub fn from_boc(data: &[u8]) -> Result<Cell> {
+        let mut roots = deserialize(data)?;
+        if roots.len() != 1 {
+            return Err(BocError::InvalidHeader("expected a single root"));
+        }
+        Ok(roots.remove(0))
+    }
+}
+
+fn read_cell<'a>(reader: &mut Reader<'a>, ref_size: usize, index: usize, cells_count: usize) -> Result<RawCell<'a>> {
+    let d1 = reader.read_u8()?;
+    let d2 = reader.read_u8()?;
+    let refs_count = (d1 & 7) as usize;
+    let exotic = d1 & 8 != 0;
+    let with_hashes = d1 & 16 != 0;
+    let level_mask = d1 >> 5;
+    if refs_count > 4 {
+        return Err(BocError::InvalidCell("absent cells are not supported"));
+    }
+    if with_hashes {
+        let hashes_count = level_mask.count_ones() as usize + 1;
+        reader.read_bytes(hashes_count * (32 + 2))?;
+    }
+    let data = reader.read_bytes((d2 as usize).div_ceil(2))?;
+    let bit_len = if d2 % 2 == 0 {
+        data.len() * 8
+    } else {
+        // the last byte is completed with a single set bit followed by zeros
+        let last = *data.last().unwrap();
+        if last == 0 {
+            return Err(BocError::InvalidCell("missing completion tag"));
+        }
+        data.len() * 8 - last.trailing_zeros() as usize - 1
+    };
+    let cell_type = if exotic {
+        data.first().and_then(|&tag| CellType::from_tag(tag)).ok_or(BocError::InvalidCell("unknown exotic cell type"))?
+    } else {
+        CellType::Ordinary
+    };
+    let mut references = Vec::with_capacity(refs_count);
+    for _ in 0..refs_count {
+        let reference = reader.read_uint(ref_size)?;
+        if reference <= index || reference >= cells_count {
+            return Err(BocError::InvalidReference(index));
+        }
+        references.push(reference);
+    }
+    Ok(RawCell { cell_type, data, bit_len, level_mask, references })
+}
@@ -0,0 +1,133 @@
+use super::cell::Cell;
+use super::slice::CellSlice;
+use super::BocError;
+
+type Result<T> = std::result::Result<T, BocError>;
+
+/// Finds the value of `key_bits`-bit `key` in a `Hashmap` with given root.
+///
+/// Returned slice starts at the leaf value, for augmented dictionaries it is preceded by the
+/// extra of the leaf.
+pub fn dict_get(root: &Cell, key: &[u8], key_bits: usize) -> Result<Option<CellSlice>> {
+    dict_get_slice(root.parse()?, key, key_bits)
+}
+
+/// Same as [`dict_get`] for a `Hashmap` stored inline in the slice
+pub fn dict_get_slice(root: CellSlice, key: &[u8], key_bits: usize) -> Result<Option<CellSlice>> {
+    if key.len() * 8 < key_bits {
+        return Err(BocError::CellUnderflow);
+    }
+    let key_bit = |i: usize| key[i / 8] & (0x80 >> (i % 8)) != 0;
+    let mut node = root;
+    let mut offset = 0;
+    loop {
+        let label = load_label(&mut node, key_bits - offset)?;
+        if label.iter().enumerate().any(|(i, &bit)| bit != key_bit(offset + i)) {
+            return Ok(None);
+        }
+        offset += label.len();
+        if offset == key_bits {
+            return Ok(Some(node));
+        }
+        let left = node.load_reference()?;
+        let right = node.load_reference()?;
+        node = if key_bit(offset) { right } else { left }.parse()?;
+        offset += 1;
+    }
+}
+
+/// Lists all entries of `key_bits`-bit `Hashmap` with given root in the order of keys.
+///
+/// Keys are packed into bytes like the keys of [`dict_get`], values are the same as returned by it.
+pub fn dict_entries(root: &Cell, key_bits: usize) -> Result<Vec<(Vec<u8>, CellSlice)>> {
+    dict_entries_slice(root.parse()?, key_bits)
+}
+
+/// Same as [`dict_entries`] for a `Hashmap` stored inline in the slice
+pub fn dict_entries_slice(root: CellSlice, key_bits: usize) -> Result<Vec<(Vec<u8>, CellSlice)>> {
+    let mut entries = Vec::new();
+    let mut stack = vec![(root, Vec::new())];
+    while let Some((mut node, mut prefix)) = stack.pop() {
+        prefix.extend(load_label(&mut node, key_bits - prefix.len())?);
+        if prefix.len() == key_bits {
+            let mut key = vec![0; key_bits.div_ceil(8)];
+            for (i, _) in prefix.iter().enumerate().filter(|(_, &bit)| bit) {
+                key[i / 8] |= 0x80 >> (i % 8);
+            }
+            entries.push((key, node));
+            continue;
+        }
+        let left = node.load_reference()?;
+        let right = node.load_reference()?;
+        // the left branch is pushed last to be visited first
+        for (bit, child) in [(true, right), (false, left)] {
+            let mut prefix = prefix.clone();
+            prefix.push(bit);
+            stack.push((child.parse()?, prefix));
+        }
+    }
+    Ok(entries)
+}
+
+/// Bits of the first `len` bits of `key`, for building dictionaries with [`make_dict`]
+#[cfg(test)]
+pub(crate) fn key_bits(key: &[u8], len: usize) -> Vec<bool> {
+    (0..len).map(|i| key[i / 8] & (0x80 >> (i % 8)) != 0).collect()
+}
+
+/// `Hashmap` with labels of the longest form, values are stored inline
+#[cfg(test)]
+pub(crate) fn make_dict(entries: &[(Vec<bool>, Cell)], key_len: usize) -> Result<Cell> {
+    let first = &entries[0].0;
+    let label_len = (0..key_len).take_while(|&i| entries.iter().all(|(key, _)| key[i] == first[i])).count();
+    let mut builder = super::CellBuilder::new();
+    builder.store_uint(0b10, 2)?.store_uint(label_len as u64, (usize::BITS - key_len.leading_zeros()) as usize)?;
+    for &bit in &first[..label_len] {
+        builder.store_bit(bit)?;
+    }
+    if label_len == key_len {
+        builder.store_slice(&entries[0].1.slice())?;
+    } else {
+        for side in [false, true] {
+            let branch: Vec<_> = entries
+                .iter()
+                .filter(|(key, _)| key[label_len] == side)
+                .map(|(key, value)| (key[label_len + 1..].to_vec(), value.clone()))
+                .collect();
+            builder.store_reference(make_dict(&branch, key_len - label_len - 1)?)?;
+        }
+    }
+    builder.build()
+}
+
+/// Loads `HmLabel` of at most `max_len` bits
+fn load_label(slice: &mut CellSlice, max_len: usize) -> Result<Vec<bool>> {
+    let len_bits = (usize::BITS - max_len.leading_zeros()) as usize;
+    let label = if !slice.load_bit()? {
+        // hml_short$0, length in unary
+        let mut len = 0;
+        while slice.load_bit()? {
+            len += 1;
+        }
+        if len > max_len {
+            return Err(BocError::InvalidCell("dictionary label is too long"));
+        }
+        (0..len).map(|_| slice.load_bit()).collect::<Result<_>>()?
+    } else if !slice.load_bit()? {
+        // hml_long$10
+        let len = slice.load_uint(len_bits)? as usize;
+        if len > max_len {
+            return Err(BocError::InvalidCell("dictionary label is too long"));
+        }
+        (0..len).map(|_| slice.load_bit()).collect::<Result<_>>()?
+    } else {
+        // hml_same$11
+        let bit = slice.load_bit()?;
+        let len = slice.load_uint(len_bits)? as usize;
+        if len > max_len {
+            return Err(BocError::InvalidCell("dictionary label is too long"));
+        }
+        vec![bit; len]
+    };
+    Ok(label)
+}
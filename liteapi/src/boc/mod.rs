@@ -0,0 +1,52 @@
+//! Bag of cells, the serialization format of TON cell trees
+mod builder;
+mod cell;
+mod de;
+mod dict;
+mod ser;
+mod slice;
+
+#[cfg(test)]
+mod tests;
+
+use crc::{Crc, CRC_32_ISCSI};
+use thiserror::Error;
+
+pub use builder::CellBuilder;
+pub use cell::{Cell, CellType, LevelMask, MAX_BITS, MAX_DEPTH, MAX_LEVEL, MAX_REFS};
+pub use de::deserialize;
+pub use dict::{dict_entries, dict_entries_slice, dict_get, dict_get_slice};
+#[cfg(test)]
+pub(crate) use dict::{key_bits, make_dict};
+pub use ser::BocWriter;
+pub use slice::CellSlice;
+
+const BOC_GENERIC_MAGIC: u32 = 0xb5ee9c72;
+const BOC_INDEXED_MAGIC: u32 = 0x68ff65f3;
+const BOC_INDEXED_CRC32C_MAGIC: u32 = 0xacc3a728;
+
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+#[derive(Debug, Error)]
+pub enum BocError {
+    #[error("Unexpected end of bag of cells")]
+    UnexpectedEof,
+    #[error("Unknown bag of cells magic {0:#010x}")]
+    UnknownMagic(u32),
+    #[error("Invalid bag of cells header: {0}")]
+    InvalidHeader(&'static str),
+    #[error("Bag of cells checksum mismatch")]
+    ChecksumMismatch,
+    #[error("Invalid cell: {0}")]
+    InvalidCell(&'static str),
+    #[error("Invalid reference in cell #{0}")]
+    InvalidReference(usize),
+    #[error("Cell overflow")]
+    CellOverflow,
+    #[error("Cell underflow")]
+    CellUnderflow,
+    #[error("Integer does not fit into given number of bits")]
+    IntegerOverflow,
+    #[error("Unexpected exotic cell {0:?}")]
+    UnexpectedExotic(CellType),
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use super::cell::Cell;
+use super::{BOC_GENERIC_MAGIC, CRC32C};
+
+const MAX_CELL_WEIGHT: usize = 64;
+
+struct CellInfo {
+    cell: Cell,
+    references: Vec<usize>,
+    weight: usize,
+    new_index: Option<usize>,
+    state: VisitState,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum VisitState {
+    New,
+    Previsited,
+    Visited,
+}
+
+/// Serializer of cell trees into bag of cells.
+///
+/// Cells are ordered in the same way as by the reference implementation, so the output is
+/// byte-for-byte identical to it.
+pub struct BocWriter {
+    roots: Vec<Cell>,
+    index: bool,
+    crc32c: bool,
+}
+
+impl BocWriter {
+    pub fn new(roots: Vec<Cell>) -> Self {
+        Self { roots, index: false, crc32c: false }
+    }
+
+    /// Adds the index of cell offsets, allowing to read cells without parsing the preceding ones
+    pub fn with_index(mut self) -> Self {
+        self.index = true;
+        self
+    }
+
+    /// Appends CRC32C checksum of the serialized data
+    pub fn with_crc32c(mut self) -> Self {
+        self.crc32c = true;
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut ordering = Ordering { cells: Vec::new(), indices: HashMap::new(), allocated: Vec::new() };
+        let roots: Vec<usize> = self.roots.iter().map(|root| ordering.import(root)).collect();
+        ordering.assign_weights();
+        // roots are allocated after the cells of all the trees
+        for &root in &roots {
+            ordering.revisit(root, 0);
+            ordering.revisit(root, 1);
+        }
+        for &root in &roots {
+            ordering.revisit(root, 2);
+        }
+        let cells_count = ordering.allocated.len();
+        // cells are allocated starting from the leaves, the serialized order is reverse
+        let position = |index: usize| cells_count - 1 - ordering.cells[index].new_index.unwrap();
+        let order: Vec<usize> = ordering.allocated.iter().rev().copied().collect();
+
+        let ref_size = bytes_for(cells_count);
+        let sizes: Vec<usize> = order
+            .iter()
+            .map(|&i| 2 + ordering.cells[i].cell.data().len() + ordering.cells[i].references.len() * ref_size)
+            .collect();
+        let cells_size: usize = sizes.iter().sum();
+        let offset_size = bytes_for(cells_size);
+
+        let mut out = Vec::with_capacity(cells_size + 32);
+        out.extend_from_slice(&BOC_GENERIC_MAGIC.to_be_bytes());
+        let mut flags = ref_size as u8;
+        if self.index {
+            flags |= 0x80;
+        }
+        if self.crc32c {
+            flags |= 0x40;
+        }
+        out.push(flags);
+        out.push(offset_size as u8);
+        write_uint(&mut out, cells_count, ref_size);
+        write_uint(&mut out, roots.len(), ref_size);
+        write_uint(&mut out, 0, ref_size);
+        write_uint(&mut out, cells_size, offset_size);
+        for &root in &roots {
+            write_uint(&mut out, position(root), ref_size);
+        }
+        if self.index {
+            let mut offset = 0;
+            for size in &sizes {
+                offset += size;
+                write_uint(&mut out, offset, offset_size);
+            }
+        }
+        for &i in &order {
+            let info = &ordering.cells[i];
+            out.extend_from_slice(&info.cell.descriptors());
+            out.extend_from_slice(&info.cell.serialized_data());
+            for &reference in &info.references {
+                write_uint(&mut out, position(reference), ref_size);
+            }
+        }
+        if self.crc32c {
+            let checksum = CRC32C.checksum(&out);
+            out.extend_from_slice(&checksum.to_le_bytes());
+        }
+        out
+    }
+}
+
+impl Cell {
+    /// Serializes the tree of this cell into bag of cells without index and checksum
+    pub fn to_boc(&self) -> Vec<u8> {
+        BocWriter::new(vec![self.clone()]).to_bytes()
+    }
+}
+
+struct Ordering {
+    cells: Vec<CellInfo>,
+    indices: HashMap<[u8; 32], usize>,
+    allocated: Vec<usize>,
+}
+
+impl Ordering {
+    /// Adds the cell and its subtree once, children go first
+    fn import(&mut self, cell: &Cell) -> usize {
+        let hash = cell.repr_hash();
+        if let Some(&index) = self.indices.get(&hash) {
+            return index;
+        }
+        let references: Vec<usize> = cell.references().iter().map(|child| self.import(child)).collect();
+        let weight = 1 + references.iter().map(|&i| self.cells[i].weight).sum::<usize>();
+        self.cells.push(CellInfo {
+            cell: cell.clone(),
+            references,
+            weight: weight.min(255),
+            new_index: None,
+            state: VisitState::New,
+        });
+        self.indices.insert(hash, self.cells.len() - 1);
+        self.cells.len() - 1
+    }
+
+    /// Limits weights of subtrees, cells which exceed their limit are visited before the others
+    fn assign_weights(&mut self) {
+        for i in (0..self.cells.len()).rev() {
+            let references = self.cells[i].references.clone();
+            let count = references.len();
+            let mut unfit = count;
+            let mut sum = MAX_CELL_WEIGHT - 1;
+            let mut mask = 0;
+            for (j, &child) in references.iter().enumerate() {
+                let limit = (MAX_CELL_WEIGHT - 1 + j) / count;
+                if self.cells[child].weight <= limit {
+                    sum -= self.cells[child].weight;
+                    unfit -= 1;
+                    mask |= 1 << j;
+                }
+            }
+            // every child left in the loop below is unfit, so `unfit` is never zero there
+            for (j, &child) in references.iter().enumerate() {
+                if mask & (1 << j) == 0 {
+                    let limit = sum / unfit;
+                    sum += 1;
+                    if self.cells[child].weight > limit {
+                        self.cells[child].weight = limit;
+                    }
+                }
+            }
+        }
+        for i in 0..self.cells.len() {
+            let sum = 1 + self.cells[i].references.iter().map(|&j| self.cells[j].weight).sum::<usize>();
+            let info = &mut self.cells[i];
+            info.weight = if sum <= info.weight { sum } else { 0 };
+        }
+    }
+
+    fn is_special(&self, index: usize) -> bool {
+        self.cells[index].weight == 0
+    }
+
+    fn revisit(&mut self, index: usize, force: u8) {
+        if self.cells[index].new_index.is_some() {
+            return;
+        }
+        if force == 0 {
+            if self.cells[index].state != VisitState::New {
+                return;
+            }
+            for j in (0..self.cells[index].references.len()).rev() {
+                let child = self.cells[index].references[j];
+                let force = if self.is_special(child) { 1 } else { 0 };
+                self.revisit(child, force);
+            }
+            self.cells[index].state = VisitState::Previsited;
+            return;
+        }
+        if force > 1 {
+            self.cells[index].new_index = Some(self.allocated.len());
+            self.allocated.push(index);
+            return;
+        }
+        if self.cells[index].state == VisitState::Visited {
+            return;
+        }
+        if self.is_special(index) {
+            self.revisit(index, 0);
+        }
+        let references = self.cells[index].references.clone();
+        for &child in references.iter().rev() {
+            self.revisit(child, 1);
+        }
+        for &child in references.iter().rev() {
+            self.revisit(child, 2);
+        }
+        self.cells[index].state = VisitState::Visited;
+    }
+}
+
+fn bytes_for(value: usize) -> usize {
+    let mut bytes = 1;
+    while bytes < 8 && value >> (bytes * 8) != 0 {
+        bytes += 1;
+    }
+    bytes
+}
+
+fn write_uint(out: &mut Vec<u8>, value: usize, bytes: usize) {
+    out.extend_from_slice(&(value as u64).to_be_bytes()[8 - bytes..]);
+}
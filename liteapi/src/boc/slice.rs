@@ -0,0 +1,131 @@
+use super::cell::{Cell, CellType};
+use super::BocError;
+
+type Result<T> = std::result::Result<T, BocError>;
+
+/// Reader of the bits and references of a cell
+#[derive(Debug, Clone)]
+pub struct CellSlice {
+    cell: Cell,
+    bit_offset: usize,
+    ref_offset: usize,
+}
+
+impl CellSlice {
+    pub fn new(cell: &Cell) -> Self {
+        Self { cell: cell.clone(), bit_offset: 0, ref_offset: 0 }
+    }
+
+    pub fn cell(&self) -> &Cell {
+        &self.cell
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        self.cell.bit_len() - self.bit_offset
+    }
+
+    pub fn remaining_refs(&self) -> usize {
+        self.cell.references().len() - self.ref_offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining_bits() == 0 && self.remaining_refs() == 0
+    }
+
+    pub fn load_bit(&mut self) -> Result<bool> {
+        if self.remaining_bits() == 0 {
+            return Err(BocError::CellUnderflow);
+        }
+        let bit = self.cell.data()[self.bit_offset / 8] & (0x80 >> (self.bit_offset % 8)) != 0;
+        self.bit_offset += 1;
+        Ok(bit)
+    }
+
+    pub fn skip_bits(&mut self, bits: usize) -> Result<()> {
+        if bits > self.remaining_bits() {
+            return Err(BocError::CellUnderflow);
+        }
+        self.bit_offset += bits;
+        Ok(())
+    }
+
+    /// Loads `bits` bits packed into bytes, unused bits of the last byte are zero
+    pub fn load_bits(&mut self, bits: usize) -> Result<Vec<u8>> {
+        if bits > self.remaining_bits() {
+            return Err(BocError::CellUnderflow);
+        }
+        let mut data = vec![0; bits.div_ceil(8)];
+        for i in 0..bits {
+            if self.load_bit()? {
+                data[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        Ok(data)
+    }
+
+    pub fn load_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        self.load_bits(len * 8)
+    }
+
+    /// Loads big-endian unsigned integer of `bits` bits
+    pub fn load_uint(&mut self, bits: usize) -> Result<u64> {
+        if bits > 64 {
+            return Err(BocError::IntegerOverflow);
+        }
+        if bits > self.remaining_bits() {
+            return Err(BocError::CellUnderflow);
+        }
+        let mut value = 0;
+        for _ in 0..bits {
+            value = value << 1 | self.load_bit()? as u64;
+        }
+        Ok(value)
+    }
+
+    /// Loads signed integer of `bits` bits in two's complement
+    pub fn load_int(&mut self, bits: usize) -> Result<i64> {
+        if bits == 0 || bits > 64 {
+            return Err(BocError::IntegerOverflow);
+        }
+        let value = self.load_uint(bits)?;
+        let shift = 64 - bits;
+        Ok(((value << shift) as i64) >> shift)
+    }
+
+    /// Loads amount of nanotons stored as `VarUInteger 16`
+    pub fn load_coins(&mut self) -> Result<u128> {
+        self.load_var_uint(4)
+    }
+
+    /// Loads `VarUInteger n` with length of `len_bits` bits, values of more than 128 bits are
+    /// not supported
+    pub fn load_var_uint(&mut self, len_bits: usize) -> Result<u128> {
+        let len = self.load_uint(len_bits)? as usize;
+        if len > 16 {
+            return Err(BocError::IntegerOverflow);
+        }
+        let bytes = self.load_bytes(len)?;
+        Ok(bytes.iter().fold(0u128, |acc, &b| acc << 8 | b as u128))
+    }
+
+    pub fn load_reference(&mut self) -> Result<Cell> {
+        let cell = self.cell.reference(self.ref_offset).ok_or(BocError::CellUnderflow)?.clone();
+        self.ref_offset += 1;
+        Ok(cell)
+    }
+}
+
+impl Cell {
+    /// Slice reading this cell from the beginning
+    pub fn slice(&self) -> CellSlice {
+        CellSlice::new(self)
+    }
+
+    /// Slice of an ordinary cell, fails for exotic cells such as branches pruned from a Merkle proof
+    pub fn parse(&self) -> Result<CellSlice> {
+        if self.cell_type() != CellType::Ordinary {
+            return Err(BocError::UnexpectedExotic(self.cell_type()));
+        }
+        Ok(CellSlice::new(self))
+    }
+}
@@ -0,0 +1,124 @@
+use std::error::Error;
+
+use sha2::{Digest, Sha256};
+
+use super::*;
+
+const WALLET_V3R2_CODE: &str = "te6cckEBAQEAcQAA3v8AIN0gggFMl7ohggEznLqxn3Gw7UTQ0x/THzHXC//jBOCk8mCDCNcYINMf0x/TH/gjE7vyY+1E0NMf0x/T/9FRMrryoVFEuvKiBPkBVBBV+RDyo/gAkyDXSpbTB9QC+wDo0QGkyMsfyx/L/8ntVBC9ba0=";
+const WALLET_V4R2_CODE: &str = "te6cckECFAEAAtQAART/APSkE/S88sgLAQIBIAIDAgFIBAUE+PKDCNcYINMf0x/THwL4I7vyZO1E0NMf0x/T//QE0VFDuvKhUVG68qIF+QFUEGT5EPKj+AAkpMjLH1JAyx9SMMv/UhD0AMntVPgPAdMHIcAAn2xRkyDXSpbTB9QC+wDoMOAhwAHjACHAAuMAAcADkTDjDQOkyMsfEssfy/8QERITAubQAdDTAyFxsJJfBOAi10nBIJJfBOAC0x8hghBwbHVnvSKCEGRzdHK9sJJfBeAD+kAwIPpEAcjKB8v/ydDtRNCBAUDXIfQEMFyBAQj0Cm+hMbOSXwfgBdM/yCWCEHBsdWe6kjgw4w0DghBkc3RyupJfBuMNBgcCASAICQB4AfoA9AQw+CdvIjBQCqEhvvLgUIIQcGx1Z4MesXCAGFAEywUmzxZY+gIZ9ADLaRfLH1Jgyz8gyYBA+wAGAIpQBIEBCPRZMO1E0IEBQNcgyAHPFvQAye1UAXKwjiOCEGRzdHKDHrFwgBhQBcsFUAPPFiP6AhPLassfyz/JgED7AJJfA+ICASAKCwBZvSQrb2omhAgKBrkPoCGEcNQICEekk30pkQzmkD6f+YN4EoAbeBAUiYcVnzGEAgFYDA0AEbjJftRNDXCx+AA9sp37UTQgQFA1yH0BDACyMoHy//J0AGBAQj0Cm+hMYAIBIA4PABmtznaiaEAga5Drhf/AABmvHfaiaEAQa5DrhY/AAG7SB/oA1NQi+QAFyMoHFcv/ydB3dIAYyMsFywIizxZQBfoCFMtrEszMyXP7AMhAFIEBCPRR8qcCAHCBAQjXGPoA0z/IVCBHgQEI9FHyp4IQbm90ZXB0gBjIywXLAlAGzxZQBPoCFMtqEssfyz/Jc/sAAgBsgQEI1xj6ANM/MFIkgQEI9Fnyp4IQZHN0cnB0gBjIywXLAlAFzxZQA/oCE8tqyx8Syz/Jc/sAAAr0AMntVGliJeU=";
+
+#[test]
+fn test_empty_cell() -> Result<(), Box<dyn Error>> {
+    let cell = Cell::from_boc(&hex::decode("b5ee9c72010101010002000000")?)?;
+    assert_eq!(cell.bit_len(), 0);
+    assert!(cell.references().is_empty());
+    assert_eq!(hex::encode(cell.repr_hash()), "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7");
+    assert_eq!(cell.repr_depth(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_wallet_code() -> Result<(), Box<dyn Error>> {
+    let boc = base64::decode(WALLET_V3R2_CODE)?;
+    let cell = Cell::from_boc(&boc)?;
+    assert_eq!(cell.cell_type(), CellType::Ordinary);
+    assert_eq!(cell.bit_len(), 111 * 8);
+    assert_eq!(hex::encode(cell.repr_hash()), "84dafa449f98a6987789ba232358072bc0f76dc4524002a5d0918b9a75d2d599");
+
+    let mut corrupted = boc.clone();
+    corrupted[20] ^= 1;
+    assert!(matches!(Cell::from_boc(&corrupted), Err(BocError::ChecksumMismatch)));
+    Ok(())
+}
+
+#[test]
+fn test_pruned_branch() -> Result<(), Box<dyn Error>> {
+    let code = Cell::from_boc(&base64::decode(WALLET_V3R2_CODE)?)?;
+    let full = Cell::ordinary(vec![0b1010_0000], 3, vec![code.clone()])?;
+
+    let mut pruned_data = vec![1, 1];
+    pruned_data.extend_from_slice(&code.hash(0));
+    pruned_data.extend_from_slice(&code.depth(0).to_be_bytes());
+    let pruned = Cell::new(CellType::PrunedBranch, pruned_data, 8 * (2 + 32 + 2), vec![])?;
+    assert_eq!(pruned.level(), 1);
+    let partial = Cell::ordinary(vec![0b1010_0000], 3, vec![pruned])?;
+    assert_eq!(partial.level(), 1);
+    assert_eq!(partial.hash(0), full.repr_hash());
+    assert_ne!(partial.repr_hash(), full.repr_hash());
+
+    let mut proof_data = vec![3];
+    proof_data.extend_from_slice(&partial.hash(0));
+    proof_data.extend_from_slice(&partial.depth(0).to_be_bytes());
+    let proof = Cell::new(CellType::MerkleProof, proof_data.clone(), 8 + 256 + 16, vec![partial.clone()])?;
+    assert_eq!(proof.level(), 0);
+
+    // level 1 hash: d1 with the level mask applied, d2, level 0 hash, then the child at level 1
+    let mut hasher = Sha256::new();
+    hasher.update([0x21, 0x01]);
+    hasher.update(partial.hash(0));
+    hasher.update(0u16.to_be_bytes());
+    hasher.update(partial.references()[0].repr_hash());
+    let partial_hash: [u8; 32] = hasher.finalize().into();
+    assert_eq!(partial.repr_hash(), partial_hash);
+    // Merkle proof is hashed over the level 1 hash of its child
+    let mut hasher = Sha256::new();
+    hasher.update([0x09, 0x46]);
+    hasher.update(&proof_data);
+    hasher.update(1u16.to_be_bytes());
+    hasher.update(partial_hash);
+    assert_eq!(proof.repr_hash(), <[u8; 32]>::from(hasher.finalize()));
+
+    proof_data[1] ^= 1;
+    assert!(Cell::new(CellType::MerkleProof, proof_data, 8 + 256 + 16, vec![partial]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_serialize() -> Result<(), Box<dyn Error>> {
+    let boc = base64::decode(WALLET_V3R2_CODE)?;
+    let cell = Cell::from_boc(&boc)?;
+    assert_eq!(BocWriter::new(vec![cell.clone()]).with_crc32c().to_bytes(), boc);
+    assert_eq!(Cell::from_boc(&BocWriter::new(vec![cell.clone()]).with_index().to_bytes())?, cell);
+
+    let boc = base64::decode(WALLET_V4R2_CODE)?;
+    let cell = Cell::from_boc(&boc)?;
+    assert_eq!(hex::encode(cell.repr_hash()), "feb5ff6820e2ff0d9483e7e0d62c817d846789fb4ae580c878866d959dabd5c0");
+    assert_eq!(BocWriter::new(vec![cell]).with_crc32c().to_bytes(), boc);
+    Ok(())
+}
+
+#[test]
+fn test_serialize_roots() -> Result<(), Box<dyn Error>> {
+    let leaf = |byte: u8| Cell::ordinary(vec![byte], 8, vec![]);
+    let first = Cell::ordinary(vec![0xaa], 8, vec![leaf(1)?])?;
+    let second = Cell::ordinary(vec![0xbb], 8, vec![leaf(2)?])?;
+    // both roots go before the cells they refer to
+    let boc = hex::decode("b5ee9c7201010402000e01000102bb020102aa03000202000201")?;
+    assert_eq!(BocWriter::new(vec![first.clone(), second.clone()]).to_bytes(), boc);
+    assert_eq!(deserialize(&boc)?, [first, second]);
+    Ok(())
+}
+
+#[test]
+fn test_builder() -> Result<(), Box<dyn Error>> {
+    let child = CellBuilder::new().store_uint(0xdead, 16)?.build()?;
+    let cell = CellBuilder::new()
+        .store_bit(true)?
+        .store_int(-5, 7)?
+        .store_coins(1_000_000_000)?
+        .store_bytes(b"ton")?
+        .store_reference(child.clone())?
+        .build()?;
+    assert_eq!(Cell::from_boc(&cell.to_boc())?, cell);
+
+    let mut slice = cell.slice();
+    assert!(slice.load_bit()?);
+    assert_eq!(slice.load_int(7)?, -5);
+    assert_eq!(slice.load_coins()?, 1_000_000_000);
+    assert_eq!(slice.load_bytes(3)?, b"ton");
+    assert_eq!(slice.load_reference()?, child);
+    assert!(slice.is_empty());
+    assert!(matches!(slice.load_bit(), Err(BocError::CellUnderflow)));
+    assert!(matches!(CellBuilder::new().store_uint(4, 2), Err(BocError::IntegerOverflow)));
+    Ok(())
+}
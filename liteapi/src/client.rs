@@ -1,289 +1,445 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
 use adnl::AdnlPeer;
 use tokio::net::ToSocketAddrs;
 use tokio_tower::multiplex;
-use tower::{Service as _, ServiceBuilder, ServiceExt as _};
+use futures::FutureExt as _;
+use tower::{buffer::Buffer, retry::Retry, util::BoxService, ServiceBuilder, ServiceExt as _};
 
-use crate::{layers::{UnwrapErrorLayer, WrapMessagesLayer}, peer::LitePeer, tl::{common::*, request::*, response::*, utils::FromResponse}, types::LiteError};
+use crate::{block::{BlockchainConfig, ValidatorSet}, keepalive::{Keepalive, KeepaliveService}, layers::{RetryPolicy, UnwrapErrorLayer, WrapMessagesLayer}, peer::LitePeer, proof::{self, ProofError, VerifiedAccountState}, snapshot::BlockSnapshot, tl::{common::*, request::*, response::*, utils::FromResponse}, tvm::{self, serialize_stack, GetMethodResult, TvmStackEntry}, types::{LiteError, LiteService}};
 
 type Result<T> = std::result::Result<T, LiteError>;
 
+/// Liteserver client, cheap to clone and usable from many tasks at once.
+///
+/// All clones share the same connection, queries made concurrently are multiplexed over it.
 pub struct LiteClient {
-    inner: tower::util::BoxService<
-        WrappedRequest,
-        Response,
-        LiteError,
-    >,
-    wait_seqno: Option<u32>,
+    inner: BufferedService,
+    next_wait_seqno: Mutex<Option<u32>>,
+    default_wait_seqno: Option<u32>,
+    wait_seqno_timeout: Duration,
+    next_timeout: Mutex<Option<Duration>>,
+    default_timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
 }
 
-impl LiteClient {
-    pub async fn connect<A: ToSocketAddrs>(address: A, public_key: impl AsRef<[u8]>) -> Result<Self> {
-        let adnl = AdnlPeer::connect(public_key, address).await?;
-        let lite = LitePeer::new(adnl);
+impl Clone for LiteClient {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            next_wait_seqno: Mutex::new(*self.next_wait_seqno.lock().unwrap()),
+            default_wait_seqno: self.default_wait_seqno,
+            wait_seqno_timeout: self.wait_seqno_timeout,
+            next_timeout: Mutex::new(*self.next_timeout.lock().unwrap()),
+            default_timeout: self.default_timeout,
+            retry: self.retry.clone(),
+        }
+    }
+}
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const WAIT_SEQNO_TIMEOUT: Duration = Duration::from_secs(10);
+const BUFFER_SIZE: usize = 100;
+
+pub(crate) type BufferedService = Buffer<BoxService<WrappedRequest, Response, LiteError>, WrappedRequest>;
+
+pub(crate) async fn connect_service<A: ToSocketAddrs>(address: A, public_key: impl AsRef<[u8]>, keepalive: Option<&Keepalive>) -> Result<BoxService<WrappedRequest, Response, LiteError>> {
+    let adnl = AdnlPeer::connect(public_key, address).await?;
+    let lite = LitePeer::new(adnl);
+    let client = multiplex::Client::<_, LiteError, _>::with_error_handler(lite, |e| {
+        log::error!("Liteserver connection failed: {:?}", e);
+    });
+    if let Some(keepalive) = keepalive {
+        // pings are sent by a background task, so the connection has to be shared with it
+        let client = Buffer::new(client, BUFFER_SIZE).map_err(LiteError::from_boxed);
+        let guard = keepalive.start(client.clone());
+        let service = ServiceBuilder::new()
+            .layer(UnwrapErrorLayer)
+            .layer(WrapMessagesLayer)
+            .service(client);
+        Ok(KeepaliveService::new(service, keepalive.clone(), guard).boxed())
+    } else {
         let service = ServiceBuilder::new()
             .layer(UnwrapErrorLayer)
             .layer(WrapMessagesLayer)
-            .service(multiplex::Client::<_, Box<dyn std::error::Error + Send + Sync + 'static>, _>::new(lite));
-        Ok(Self { inner: service.boxed(), wait_seqno: None })
+            .service(client);
+        Ok(service.boxed())
+    }
+}
+
+/// Connects with a timeout and wraps the connection into a cloneable buffered service
+pub(crate) async fn connect_buffered<A: ToSocketAddrs>(address: A, public_key: impl AsRef<[u8]>, keepalive: Option<&Keepalive>) -> Result<BufferedService> {
+    let service = tokio::time::timeout(CONNECT_TIMEOUT, connect_service(address, public_key, keepalive))
+        .await
+        .map_err(|_| LiteError::Timeout)??;
+    Ok(Buffer::new(service, BUFFER_SIZE))
+}
+
+impl LiteClient {
+    pub async fn connect<A: ToSocketAddrs>(address: A, public_key: impl AsRef<[u8]>) -> Result<Self> {
+        Ok(Self::new(connect_service(address, public_key, None).await?))
+    }
+
+    /// Connects with a background heartbeat, see [`Keepalive`]
+    pub async fn connect_with_keepalive<A: ToSocketAddrs>(address: A, public_key: impl AsRef<[u8]>, keepalive: &Keepalive) -> Result<Self> {
+        Ok(Self::new(connect_service(address, public_key, Some(keepalive)).await?))
+    }
+
+    /// Creates a client on top of an arbitrary lite service, e.g. [`crate::pool::LitePool`] or [`crate::reconnect::Reconnect`].
+    ///
+    /// Must be called within Tokio runtime, since the service is driven by a background task.
+    pub fn new<S>(service: S) -> Self
+    where
+        S: LiteService + Send + 'static,
+        S::Future: Send + 'static,
+    {
+        Self {
+            inner: Buffer::new(BoxService::new(service), BUFFER_SIZE),
+            next_wait_seqno: Mutex::new(None),
+            default_wait_seqno: None,
+            wait_seqno_timeout: WAIT_SEQNO_TIMEOUT,
+            next_timeout: Mutex::new(None),
+            default_timeout: None,
+            retry: None,
+        }
     }
 
+    /// Sets the deadline for every request, unless overridden by [`LiteClient::timeout`]
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the deadline for the next request only
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        *self.next_timeout.get_mut().unwrap() = Some(timeout);
+        self
+    }
+
+    /// Repeats queries failed with transient errors according to the given policy.
+    ///
+    /// The deadline set by [`LiteClient::timeout`] or [`LiteClient::default_timeout`] covers all the attempts.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Sets how long liteserver waits for the seqno given to [`LiteClient::wait_masterchain_seqno`]
+    /// or [`LiteClient::default_wait_masterchain_seqno`]
+    pub fn wait_seqno_timeout(mut self, timeout: Duration) -> Self {
+        self.wait_seqno_timeout = timeout;
+        self
+    }
+
+    /// Makes liteserver wait for the masterchain block with given seqno before answering
+    /// the next request
     pub fn wait_masterchain_seqno(mut self, seqno: u32) -> Self {
-        self.wait_seqno = Some(seqno);
+        *self.next_wait_seqno.get_mut().unwrap() = Some(seqno);
         self
     }
 
-    async fn send_request<T: FromResponse>(&mut self, request: Request) -> Result<T>
-    {
+    /// Makes liteserver wait for the masterchain block with given seqno before answering
+    /// every request, unless overridden by [`LiteClient::wait_masterchain_seqno`]
+    pub fn default_wait_masterchain_seqno(mut self, seqno: u32) -> Self {
+        self.default_wait_seqno = Some(seqno);
+        self
+    }
+
+    /// Pins queries to the given masterchain block, see [`BlockSnapshot`]
+    pub fn at(&self, mc_block: BlockIdExt) -> BlockSnapshot {
+        BlockSnapshot::new(self.clone(), mc_block)
+    }
+
+    fn wait_masterchain_seqno_prefix(&self) -> Option<WaitMasterchainSeqno> {
+        let timeout_ms = self.wait_seqno_timeout.as_millis().try_into().unwrap_or(u32::MAX);
+        let seqno = self.next_wait_seqno.lock().unwrap().take().or(self.default_wait_seqno);
+        seqno.map(|seqno| WaitMasterchainSeqno { seqno, timeout_ms })
+    }
+
+    async fn call(&self, wrapped_request: WrappedRequest) -> Result<Response> {
+        let inner = self.inner.clone().map_err(LiteError::from_boxed);
+        let response = match &self.retry {
+            Some(policy) => Retry::new(policy.clone(), inner).oneshot(wrapped_request).boxed(),
+            None => inner.oneshot(wrapped_request).boxed(),
+        };
+        let timeout = self.next_timeout.lock().unwrap().take().or(self.default_timeout);
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, response).await.map_err(|_| LiteError::Timeout)?,
+            None => response.await,
+        }
+    }
+
+    /// Sends an arbitrary request and converts the answer to `T`.
+    ///
+    /// Use `query::<Response>` to get the answer as is.
+    pub async fn query<T: FromResponse>(&self, request: Request) -> Result<T> {
+        let wrapped_request = WrappedRequest {
+            wait_masterchain_seqno: self.wait_masterchain_seqno_prefix(),
+            request,
+        };
+        T::from_response(self.call(wrapped_request).await?)
+    }
+
+    /// Sends a pre-serialized request and returns the serialized answer.
+    ///
+    /// `data` is the content of `liteServer.query`, i.e. a TL request optionally prefixed with
+    /// `liteServer.waitMasterchainSeqno`. It is sent as is and the answer is returned undecoded, so
+    /// requests unknown to this crate can be made too. Liteserver errors are returned as serialized
+    /// `liteServer.error`, like any other answer.
+    pub async fn query_raw(&self, data: &[u8]) -> Result<Vec<u8>> {
         let wrapped_request = WrappedRequest {
-            wait_masterchain_seqno: self.wait_seqno.take().map(|seqno| WaitMasterchainSeqno { seqno, timeout_ms: 10000 }),
-            request: request.into(),
+            wait_masterchain_seqno: None,
+            request: Request::Raw(data.to_vec()),
         };
-        T::from_response(self.inner.ready().await?.call(wrapped_request).await?)
+        match self.call(wrapped_request).await? {
+            Response::Raw(answer) => Ok(answer),
+            _ => Err(LiteError::UnexpectedMessage),
+        }
     }
 
-    pub async fn get_masterchain_info(&mut self) -> Result<MasterchainInfo> {
-        let response: MasterchainInfo = self.send_request(Request::GetMasterchainInfo).await?;
+    pub async fn get_masterchain_info(&self) -> Result<MasterchainInfo> {
+        let response: MasterchainInfo = self.query(Request::GetMasterchainInfo).await?;
         Ok(response)
     }
 
-    pub async fn get_masterchain_info_ext(&mut self, mode: u32) -> Result<MasterchainInfoExt> {
+    pub async fn get_masterchain_info_ext(&self, mode: u32) -> Result<MasterchainInfoExt> {
         let request = Request::GetMasterchainInfoExt(GetMasterchainInfoExt { mode });
-        let response: MasterchainInfoExt = self.send_request(request).await?;
+        let response: MasterchainInfoExt = self.query(request).await?;
         Ok(response)
     }
 
-    pub async fn get_time(&mut self) -> Result<u32> {
-        let response: CurrentTime = self.send_request(Request::GetTime).await?;
+    pub async fn get_time(&self) -> Result<u32> {
+        let response: CurrentTime = self.query(Request::GetTime).await?;
         Ok(response.now)
     }
 
-    pub async fn get_version(&mut self) -> Result<Version> {
-        let response: Version = self.send_request(Request::GetVersion).await?;
+    pub async fn get_version(&self) -> Result<Version> {
+        let response: Version = self.query(Request::GetVersion).await?;
         Ok(response)
     }
 
-    pub async fn get_block(&mut self, id: BlockIdExt) -> Result<Vec<u8>> {
+    pub async fn get_block(&self, id: BlockIdExt) -> Result<Vec<u8>> {
         let request = Request::GetBlock(GetBlock { id });
-        let response: BlockData = self.send_request(request).await?;
+        let response: BlockData = self.query(request).await?;
         Ok(response.data)
     }
 
-    pub async fn get_state(&mut self, id: BlockIdExt) -> Result<BlockState> {
+    pub async fn get_state(&self, id: BlockIdExt) -> Result<BlockState> {
         let request = Request::GetState(GetState { id });
-        let response: BlockState = self.send_request(request).await?;
+        let response: BlockState = self.query(request).await?;
         Ok(response)
     }
 
-pub async fn get_block_header(
-    &mut self,
-    id: BlockIdExt,
-    with_state_update: bool,
-    with_value_flow: bool,
-    with_extra: bool,
-    with_shard_hashes: bool,
-    with_prev_blk_signatures: bool,
-) -> Result<Vec<u8>> {
-        let request = Request::GetBlockHeader(GetBlockHeader {
-            id,
-            mode: (),
-            with_state_update: if with_state_update { Some(()) } else { None },
-            with_value_flow: if with_value_flow { Some(()) } else { None },
-            with_extra: if with_extra { Some(()) } else { None },
-            with_shard_hashes: if with_shard_hashes { Some(()) } else { None },
-            with_prev_blk_signatures: if with_prev_blk_signatures { Some(()) } else { None },
-        });
-        let response: BlockHeader = self.send_request(request).await?;
+    pub async fn get_block_header(&self, request: GetBlockHeader) -> Result<Vec<u8>> {
+        let response: BlockHeader = self.query(Request::GetBlockHeader(request)).await?;
         Ok(response.header_proof)
     }
 
-    pub async fn send_message(&mut self, body: Vec<u8>) -> Result<u32> {
+    pub async fn send_message(&self, body: Vec<u8>) -> Result<u32> {
         let request = Request::SendMessage(SendMessage { body });
-        let response: SendMsgStatus = self.send_request(request).await?;
+        let response: SendMsgStatus = self.query(request).await?;
         Ok(response.status)
     }
 
-    pub async fn get_account_state(&mut self, id: BlockIdExt, account: AccountId) -> Result<AccountState> {
+    pub async fn get_account_state(&self, id: BlockIdExt, account: AccountId) -> Result<AccountState> {
         let request = Request::GetAccountState(GetAccountState { id, account });
-        let response: AccountState = self.send_request(request).await?;
+        let response: AccountState = self.query(request).await?;
+        Ok(response)
+    }
+
+    /// Same as [`LiteClient::get_account_state`], but the state is checked against Merkle proofs
+    /// rooted at `id`, so liteserver does not have to be trusted
+    pub async fn get_account_state_verified(&self, id: BlockIdExt, account: AccountId) -> Result<VerifiedAccountState> {
+        let response = self.get_account_state(id.clone(), account.clone()).await?;
+        Ok(proof::check_account_state(&id, &account, &response)?)
+    }
+
+    /// Same as [`LiteClient::get_account_state`], but the state is returned with pruned branches
+    pub async fn get_account_state_prunned(&self, id: BlockIdExt, account: AccountId) -> Result<AccountState> {
+        let request = Request::GetAccountStatePrunned(GetAccountState { id, account });
+        let response: AccountState = self.query(request).await?;
         Ok(response)
     }
 
-    pub async fn run_smc_method(&mut self, mode: u32, id: BlockIdExt, account: AccountId, method_id: u64, params: Vec<u8>) -> Result<RunMethodResult> {
-        let request = Request::RunSmcMethod(RunSmcMethod { mode, id, account, method_id, params });
-        let response: RunMethodResult = self.send_request(request).await?;
+    /// Runs get-method with the parts of the answer requested by [`RunSmcMethod`] flags.
+    ///
+    /// `run_smc_method(4, id, account, method_id, params)` of earlier versions is
+    /// `run_smc_method(RunSmcMethod::new(id, account, method_id, params).with_result())`, the other bits
+    /// of `mode` have their own setters.
+    pub async fn run_smc_method(&self, request: RunSmcMethod) -> Result<RunMethodResult> {
+        let response: RunMethodResult = self.query(Request::RunSmcMethod(request)).await?;
         Ok(response)
     }
 
-    pub async fn get_shard_info(&mut self, id: BlockIdExt, workchain: i32, shard: u64, exact: bool) -> Result<ShardInfo> {
+    /// Runs get-method of the account with the stack of `params`, the last entry is the top
+    /// of the stack
+    pub async fn run_get_method(&self, id: BlockIdExt, account: AccountId, method_id: u64, params: &[TvmStackEntry]) -> Result<GetMethodResult> {
+        let params = serialize_stack(params)?.to_boc();
+        let response = self.run_smc_method(RunSmcMethod::new(id, account, method_id, params).with_result()).await?;
+        Ok(GetMethodResult::from_response(&response)?)
+    }
+
+    /// Same as [`LiteClient::run_get_method`], but the account state used by the get-method is
+    /// checked against Merkle proofs rooted at `id`, see [`proof::check_run_method_result`]
+    pub async fn run_get_method_verified(&self, id: BlockIdExt, account: AccountId, method_id: u64, params: &[TvmStackEntry]) -> Result<(VerifiedAccountState, GetMethodResult)> {
+        let params = serialize_stack(params)?.to_boc();
+        let request = RunSmcMethod::new(id.clone(), account.clone(), method_id, params).with_proofs().with_state_proof().with_result();
+        let response = self.run_smc_method(request).await?;
+        let state = proof::check_run_method_result(&id, &account, &response)?;
+        Ok((state, GetMethodResult::from_response(&response)?))
+    }
+
+    /// Same as [`LiteClient::run_get_method`] with the id of get-method computed from its name
+    pub async fn run_get_method_by_name(&self, id: BlockIdExt, account: AccountId, name: &str, params: &[TvmStackEntry]) -> Result<GetMethodResult> {
+        self.run_get_method(id, account, tvm::method_id(name), params).await
+    }
+
+    pub async fn get_shard_info(&self, id: BlockIdExt, workchain: i32, shard: u64, exact: bool) -> Result<ShardInfo> {
         let request = Request::GetShardInfo(GetShardInfo { id, workchain, shard, exact });
-        let response: ShardInfo = self.send_request(request).await?;
+        let response: ShardInfo = self.query(request).await?;
         Ok(response)
     }
 
-    pub async fn get_all_shards_info(&mut self, id: BlockIdExt) -> Result<AllShardsInfo> {
+    pub async fn get_all_shards_info(&self, id: BlockIdExt) -> Result<AllShardsInfo> {
         let request = Request::GetAllShardsInfo(GetAllShardsInfo { id });
-        let response: AllShardsInfo = self.send_request(request).await?;
+        let response: AllShardsInfo = self.query(request).await?;
         Ok(response)
     }
 
-    pub async fn get_one_transaction(&mut self, id: BlockIdExt, account: AccountId, lt: u64) -> Result<TransactionInfo> {
+    pub async fn get_one_transaction(&self, id: BlockIdExt, account: AccountId, lt: u64) -> Result<TransactionInfo> {
         let request = Request::GetOneTransaction(GetOneTransaction { id, account, lt });
-        let response: TransactionInfo = self.send_request(request).await?;
+        let response: TransactionInfo = self.query(request).await?;
         Ok(response)
     }
 
-    pub async fn get_transactions(&mut self, count: u32, account: AccountId, lt: u64, hash: Int256) -> Result<TransactionList> {
+    pub async fn get_transactions(&self, count: u32, account: AccountId, lt: u64, hash: Int256) -> Result<TransactionList> {
         let request = Request::GetTransactions(GetTransactions { count, account, lt, hash });
-        let response: TransactionList = self.send_request(request).await?;
+        let response: TransactionList = self.query(request).await?;
         Ok(response)
     }
 
-pub async fn lookup_block(
-    &mut self,
-    mode: (),
-    id: BlockId,
-    seqno: Option<()>,
-    lt: Option<u64>,
-    utime: Option<u32>,
-    with_state_update: bool,
-    with_value_flow: bool,
-    with_extra: bool,
-    with_shard_hashes: bool,
-    with_prev_blk_signatures: bool,
-) -> Result<BlockHeader> {
-        let request = Request::LookupBlock(LookupBlock {
-            mode,
-            id,
-            seqno,
-            lt,
-            utime,
-        with_state_update: if with_state_update { Some(()) } else { None },
-        with_value_flow: if with_value_flow { Some(()) } else { None },
-        with_extra: if with_extra { Some(()) } else { None },
-        with_shard_hashes: if with_shard_hashes { Some(()) } else { None },
-        with_prev_blk_signatures: if with_prev_blk_signatures { Some(()) } else { None },
-    });
-        let response: BlockHeader = self.send_request(request).await?;
+    pub async fn lookup_block(&self, request: LookupBlock) -> Result<BlockHeader> {
+        let response: BlockHeader = self.query(Request::LookupBlock(request)).await?;
         Ok(response)
     }
 
-pub async fn list_block_transactions(
-    &mut self,
-    id: BlockIdExt,
-    count: u32,
-    after: Option<TransactionId3>,
-    reverse_order: bool,
-    want_proof: bool
-) -> Result<BlockTransactions> {
-    let request = Request::ListBlockTransactions(ListBlockTransactions {
-        id,
-        mode: (),
-        count,
-        after,
-        reverse_order: if reverse_order { Some(()) } else { None },
-        want_proof: if want_proof { Some(()) } else { None },
-    });
-        let response: BlockTransactions = self.send_request(request).await?;
+    pub async fn lookup_block_with_proof(&self, request: LookupBlockWithProof) -> Result<LookupBlockResult> {
+        let response: LookupBlockResult = self.query(Request::LookupBlockWithProof(request)).await?;
+        Ok(response)
+    }
+
+    pub async fn list_block_transactions(&self, request: ListBlockTransactions) -> Result<BlockTransactions> {
+        let response: BlockTransactions = self.query(Request::ListBlockTransactions(request)).await?;
+        Ok(response)
+    }
+
+    /// Same as [`LiteClient::list_block_transactions`], but returns serialized transactions instead of their ids
+    pub async fn list_block_transactions_ext(&self, request: ListBlockTransactions) -> Result<BlockTransactionsExt> {
+        let response: BlockTransactionsExt = self.query(Request::ListBlockTransactionsExt(request)).await?;
         Ok(response)
     }
 
-pub async fn get_block_proof(
-    &mut self,
-    known_block: BlockIdExt,
-    target_block: Option<BlockIdExt>,
-    allow_weak_target: bool,
-    base_block_from_request: bool
-) -> Result<PartialBlockProof> {
-        let request = Request::GetBlockProof(GetBlockProof {
-            mode: (),
-            known_block,
-            target_block,
-        allow_weak_target: if allow_weak_target { Some(()) } else { None },
-        base_block_from_request: if base_block_from_request { Some(()) } else { None },
-        });
-        let response: PartialBlockProof = self.send_request(request).await?;
+    pub async fn get_block_proof(&self, request: GetBlockProof) -> Result<PartialBlockProof> {
+        let response: PartialBlockProof = self.query(Request::GetBlockProof(request)).await?;
         Ok(response)
     }
 
-pub async fn get_config_all(
-    &mut self,
-    id: BlockIdExt,
-    with_state_root: bool,
-    with_libraries: bool,
-    with_state_extra_root: bool,
-    with_shard_hashes: bool,
-    with_validator_set: bool,
-    with_special_smc: bool,
-    with_accounts_root: bool,
-    with_prev_blocks: bool,
-    with_workchain_info: bool,
-    with_capabilities: bool,
-    extract_from_key_block: bool,
-) -> Result<ConfigInfo> {
-        let request = Request::GetConfigAll(GetConfigAll {
-            mode: (),
-            id,
-            with_state_root: if with_state_root { Some(()) } else { None },
-            with_libraries: if with_libraries { Some(()) } else { None },
-            with_state_extra_root: if with_state_extra_root { Some(()) } else { None },
-            with_shard_hashes: if with_shard_hashes { Some(()) } else { None },
-            with_validator_set: if with_validator_set { Some(()) } else { None },
-            with_special_smc: if with_special_smc { Some(()) } else { None },
-            with_accounts_root: if with_accounts_root { Some(()) } else { None },
-            with_prev_blocks: if with_prev_blocks { Some(()) } else { None },
-            with_workchain_info: if with_workchain_info { Some(()) } else { None },
-            with_capabilities: if with_capabilities { Some(()) } else { None },
-            extract_from_key_block: if extract_from_key_block { Some(()) } else { None },
-        });
-        let response: ConfigInfo = self.send_request(request).await?;
+    /// Proves masterchain block `target_block`, or the last block of liteserver, starting from the
+    /// `trusted` block, see [`proof::check_partial_block_proof`]. Returns the proven block.
+    pub async fn get_block_proof_verified(&self, trusted: BlockIdExt, target_block: Option<BlockIdExt>) -> Result<BlockIdExt> {
+        let mut known = trusted;
+        loop {
+            let request = GetBlockProof { target_block: target_block.clone(), ..GetBlockProof::new(known.clone()) };
+            let response = self.get_block_proof(request).await?;
+            let proven = proof::check_partial_block_proof(&known, &response)?;
+            if response.complete {
+                if target_block.as_ref().is_some_and(|target| *target != proven) {
+                    return Err(ProofError::InvalidChain("proof ends at another block").into());
+                }
+                return Ok(proven);
+            }
+            // liteserver proves long chains in parts
+            if proven == known {
+                return Err(ProofError::InvalidChain("incomplete proof without links").into());
+            }
+            known = proven;
+        }
+    }
+
+    pub async fn get_config_all(&self, request: GetConfigAll) -> Result<ConfigInfo> {
+        let response: ConfigInfo = self.query(Request::GetConfigAll(request)).await?;
         Ok(response)
     }
 
-pub async fn get_config_params(
-    &mut self,
-    id: BlockIdExt,
-    param_list: Vec<i32>,
-    with_state_root: bool,
-    with_libraries: bool,
-    with_state_extra_root: bool,
-    with_shard_hashes: bool,
-    with_validator_set: bool,
-    with_special_smc: bool,
-    with_accounts_root: bool,
-    with_prev_blocks: bool,
-    with_workchain_info: bool,
-    with_capabilities: bool,
-    extract_from_key_block: bool,
-) -> Result<ConfigInfo> {
-        let request = Request::GetConfigParams(GetConfigParams {
-            mode: (),
-            id,
-            param_list,
-            with_state_root: if with_state_root { Some(()) } else { None },
-            with_libraries: if with_libraries { Some(()) } else { None },
-            with_state_extra_root: if with_state_extra_root { Some(()) } else { None },
-            with_shard_hashes: if with_shard_hashes { Some(()) } else { None },
-            with_validator_set: if with_validator_set { Some(()) } else { None },
-            with_special_smc: if with_special_smc { Some(()) } else { None },
-            with_accounts_root: if with_accounts_root { Some(()) } else { None },
-            with_prev_blocks: if with_prev_blocks { Some(()) } else { None },
-            with_workchain_info: if with_workchain_info { Some(()) } else { None },
-            with_capabilities: if with_capabilities { Some(()) } else { None },
-            extract_from_key_block: if extract_from_key_block { Some(()) } else { None },
-        });
-        let response: ConfigInfo = self.send_request(request).await?;
+    pub async fn get_config_params(&self, request: GetConfigParams) -> Result<ConfigInfo> {
+        let response: ConfigInfo = self.query(Request::GetConfigParams(request)).await?;
         Ok(response)
     }
 
-    pub async fn get_validator_stats(&mut self, id: BlockIdExt, limit: u32, start_after: Option<Int256>, modified_after: Option<u32>) -> Result<ValidatorStats> {
+    /// Config params of masterchain block `id`, the config is checked against Merkle proofs
+    /// rooted at `id`
+    pub async fn get_config_params_verified(&self, id: BlockIdExt, param_list: Vec<i32>) -> Result<BlockchainConfig> {
+        let response = self.get_config_params(GetConfigParams::new(id.clone(), param_list)).await?;
+        Ok(BlockchainConfig::new(proof::check_config_info(&id, &response)?))
+    }
+
+    /// Same as [`LiteClient::get_config_params_verified`] for all config params
+    pub async fn get_config_all_verified(&self, id: BlockIdExt) -> Result<BlockchainConfig> {
+        let response = self.get_config_all(GetConfigAll::new(id.clone())).await?;
+        Ok(BlockchainConfig::new(proof::check_config_info(&id, &response)?))
+    }
+
+    /// Validator set from config param 32 (previous), 34 (current) or 36 (next) of masterchain
+    /// block `id`, checked against Merkle proofs rooted at `id`. `None` if the param is not set.
+    pub async fn get_validator_set_verified(&self, id: BlockIdExt, index: u32) -> Result<Option<ValidatorSet>> {
+        let config = self.get_config_params_verified(id, vec![index as i32]).await?;
+        Ok(config.validator_set(index).map_err(ProofError::from)?)
+    }
+
+    pub async fn get_validator_stats(&self, id: BlockIdExt, limit: u32, start_after: Option<Int256>, modified_after: Option<u32>) -> Result<ValidatorStats> {
         let request = Request::GetValidatorStats(GetValidatorStats { mode: (), id, limit, start_after, modified_after });
-        let response: ValidatorStats = self.send_request(request).await?;
+        let response: ValidatorStats = self.query(request).await?;
         Ok(response)
     }
 
-    pub async fn get_libraries(&mut self, library_list: Vec<Int256>) -> Result<Vec<LibraryEntry>> {
+    pub async fn get_libraries(&self, library_list: Vec<Int256>) -> Result<Vec<LibraryEntry>> {
         let request = Request::GetLibraries(GetLibraries { library_list });
-        let response: LibraryResult = self.send_request(request).await?;
+        let response: LibraryResult = self.query(request).await?;
         Ok(response.result)
     }
+
+    pub async fn get_libraries_with_proof(&self, id: BlockIdExt, library_list: Vec<Int256>) -> Result<LibraryResultWithProof> {
+        let request = Request::GetLibrariesWithProof(GetLibrariesWithProof { id, mode: (), library_list });
+        let response: LibraryResultWithProof = self.query(request).await?;
+        Ok(response)
+    }
+
+    pub async fn get_shard_block_proof(&self, id: BlockIdExt) -> Result<ShardBlockProof> {
+        let request = Request::GetShardBlockProof(GetShardBlockProof { id });
+        let response: ShardBlockProof = self.query(request).await?;
+        Ok(response)
+    }
+
+    /// Outbound message queue sizes of the given shard, or of all shards if `shard_id` is not specified
+    pub async fn get_out_msg_queue_sizes(&self, shard_id: Option<(i32, u64)>) -> Result<OutMsgQueueSizes> {
+        let request = Request::GetOutMsgQueueSizes(GetOutMsgQueueSizes { mode: (), shard_id });
+        let response: OutMsgQueueSizes = self.query(request).await?;
+        Ok(response)
+    }
+
+    pub async fn get_block_out_msg_queue_size(&self, request: GetBlockOutMsgQueueSize) -> Result<BlockOutMsgQueueSize> {
+        let response: BlockOutMsgQueueSize = self.query(Request::GetBlockOutMsgQueueSize(request)).await?;
+        Ok(response)
+    }
+
+    pub async fn get_dispatch_queue_info(&self, request: GetDispatchQueueInfo) -> Result<DispatchQueueInfo> {
+        let response: DispatchQueueInfo = self.query(Request::GetDispatchQueueInfo(request)).await?;
+        Ok(response)
+    }
+
+    pub async fn get_dispatch_queue_messages(&self, request: GetDispatchQueueMessages) -> Result<DispatchQueueMessages> {
+        let response: DispatchQueueMessages = self.query(Request::GetDispatchQueueMessages(request)).await?;
+        Ok(response)
+    }
 }
\ No newline at end of file
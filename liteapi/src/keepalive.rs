@@ -0,0 +1,141 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use adnl::AdnlError;
+use tokio::time::MissedTickBehavior;
+use tower::{Service, ServiceExt as _};
+
+use crate::tl::adnl::Message;
+use crate::types::LiteError;
+
+#[derive(Default)]
+struct Stats {
+    latency: Mutex<Option<Duration>>,
+    missed: AtomicU32,
+    dead: AtomicBool,
+}
+
+/// Background heartbeat sending `tcp.ping` over the connection and watching for `tcp.pong`.
+///
+/// The same value is shared with the pinging task, so a clone kept by the caller
+/// reports the state of the connection.
+#[derive(Clone)]
+pub struct Keepalive {
+    interval: Duration,
+    max_missed: u32,
+    stats: Arc<Stats>,
+}
+
+impl Keepalive {
+    /// Pings every `interval` and considers the connection dead after `max_missed` pongs in a row are lost
+    pub fn new(interval: Duration, max_missed: u32) -> Self {
+        Self {
+            interval,
+            max_missed: max_missed.max(1),
+            stats: Default::default(),
+        }
+    }
+
+    /// Round-trip time of the last answered ping
+    pub fn latency(&self) -> Option<Duration> {
+        *self.stats.latency.lock().unwrap()
+    }
+
+    /// Number of pings in a row left without pong
+    pub fn missed_pongs(&self) -> u32 {
+        self.stats.missed.load(Ordering::Relaxed)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        !self.stats.dead.load(Ordering::Relaxed)
+    }
+
+    /// Starts pinging over `service` until the returned guard is dropped
+    pub(crate) fn start<S>(&self, mut service: S) -> Arc<()>
+    where
+        S: Service<Message, Response = Message, Error = LiteError> + Send + 'static,
+        S::Future: Send,
+    {
+        *self.stats.latency.lock().unwrap() = None;
+        self.stats.missed.store(0, Ordering::Relaxed);
+        self.stats.dead.store(false, Ordering::Relaxed);
+
+        let guard = Arc::new(());
+        let weak_guard: Weak<()> = Arc::downgrade(&guard);
+        let keepalive = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(keepalive.interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if weak_guard.strong_count() == 0 {
+                    break;
+                }
+                let started = Instant::now();
+                let ping = async {
+                    service.ready().await?.call(Message::Ping { random_id: 0 }).await
+                };
+                let result = tokio::time::timeout(keepalive.interval, ping).await;
+                // the connection may have been replaced while waiting, its stats are not ours anymore
+                if weak_guard.strong_count() == 0 {
+                    break;
+                }
+                match result {
+                    Ok(Ok(Message::Pong { .. })) => {
+                        let latency = started.elapsed();
+                        log::trace!("Liteserver pong received in {:?}", latency);
+                        *keepalive.stats.latency.lock().unwrap() = Some(latency);
+                        keepalive.stats.missed.store(0, Ordering::Relaxed);
+                    }
+                    result => {
+                        let missed = keepalive.stats.missed.fetch_add(1, Ordering::Relaxed) + 1;
+                        log::warn!("Liteserver pong missed ({}/{}): {:?}", missed, keepalive.max_missed, result);
+                        if missed >= keepalive.max_missed {
+                            log::error!("Liteserver connection is dead, stopping keepalive");
+                            keepalive.stats.dead.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+        guard
+    }
+}
+
+/// Lite service failing all requests with [`AdnlError::EndOfStream`] after its connection is
+/// declared dead by [`Keepalive`]
+pub struct KeepaliveService<S> {
+    service: S,
+    keepalive: Keepalive,
+    _guard: Arc<()>,
+}
+
+impl<S> KeepaliveService<S> {
+    pub(crate) fn new(service: S, keepalive: Keepalive, guard: Arc<()>) -> Self {
+        Self { service, keepalive, _guard: guard }
+    }
+}
+
+impl<S, Request> Service<Request> for KeepaliveService<S>
+where
+    S: Service<Request, Error = LiteError>,
+{
+    type Response = S::Response;
+    type Error = LiteError;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if !self.keepalive.is_alive() {
+            return Poll::Ready(Err(LiteError::AdnlError(AdnlError::EndOfStream)));
+        }
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        self.service.call(request)
+    }
+}
@@ -1,14 +1,18 @@
+mod retry;
+
 use std::task::{Context, Poll};
 
 use futures::future::{self, BoxFuture};
 use tower::{Layer, Service};
 
 use crate::tl::common::Int256;
-use crate::tl::request::LiteQuery;
+use crate::tl::request::{LiteQuery, Request};
 use crate::tl::response::Error;
 use crate::types::LiteService;
 use crate::{tl::{adnl::Message, request::WrappedRequest, response::Response}, types::LiteError};
 
+pub use retry::{RetryLayer, RetryPolicy, RetryService};
+
 pub struct WrapMessagesLayer;
 
 impl<S> Layer<S> for WrapMessagesLayer {
@@ -41,6 +45,7 @@ where
     }
 
     fn call(&mut self, request: WrappedRequest) -> Self::Future {
+        let raw = matches!(request.request, Request::Raw(_));
         let fut = self.service.call(Message::Query {
             query_id: Int256::default(), 
             query: LiteQuery {
@@ -51,6 +56,10 @@ where
             let response = fut.await.map_err(Into::into)?.into();
 
             match response {
+                // answers are read undecoded, only the ones to typed requests are decoded here
+                Message::Answer { answer: Response::Raw(data), .. } if !raw => {
+                    tl_proto::deserialize(&data).map_err(LiteError::TlError)
+                }
                 Message::Answer { answer, .. } => Ok(answer),
                 _ => Err(LiteError::UnexpectedMessage)
             }
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tower::retry::{Policy, Retry};
+
+use crate::tl::request::WrappedRequest;
+use crate::tl::response::Response;
+use crate::types::LiteError;
+
+const MAX_RETRIES: usize = 3;
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Repeats queries failed with transient errors, see [`LiteError::is_transient`].
+///
+/// Waits between attempts with exponential backoff. Messages are sent again only if liteserver
+/// explicitly refused them, since a message lost with the connection may have been delivered.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_retries: usize,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    attempt: u32,
+}
+
+/// Layer repeating failed queries according to [`RetryPolicy`]
+pub type RetryLayer = tower::retry::RetryLayer<RetryPolicy>;
+
+pub type RetryService<S> = Retry<RetryPolicy, S>;
+
+impl RetryPolicy {
+    pub fn new(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            min_backoff: MIN_BACKOFF,
+            max_backoff: MAX_BACKOFF,
+            attempt: 0,
+        }
+    }
+
+    /// Sets the delay before the first retry, doubled after each failure up to `max`
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max;
+        self
+    }
+
+    fn delay(&self) -> Duration {
+        self.min_backoff
+            .checked_mul(2u32.saturating_pow(self.attempt))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(MAX_RETRIES)
+    }
+}
+
+impl Policy<WrappedRequest, Response, LiteError> for RetryPolicy {
+    type Future = BoxFuture<'static, Self>;
+
+    fn retry(&self, request: &WrappedRequest, result: Result<&Response, &LiteError>) -> Option<Self::Future> {
+        let error = result.err()?;
+        let repeatable = request.request.is_idempotent() || matches!(error, LiteError::ServerError(_));
+        if !error.is_transient() || !repeatable || self.attempt as usize >= self.max_retries {
+            return None;
+        }
+        let delay = self.delay();
+        log::debug!("Query failed (attempt {}), retrying in {:?}: {:?}", self.attempt + 1, delay, error);
+        let next = Self { attempt: self.attempt + 1, ..self.clone() };
+        Some(Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            next
+        }))
+    }
+
+    fn clone_request(&self, request: &WrappedRequest) -> Option<WrappedRequest> {
+        Some(request.clone())
+    }
+}
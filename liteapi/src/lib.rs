@@ -1,6 +1,17 @@
 pub mod tl;
+pub mod address;
 pub mod types;
 pub mod peer;
 pub mod layers;
+pub mod boc;
+pub mod block;
+pub mod tvm;
+pub mod proof;
 pub mod client;
-pub mod server;
\ No newline at end of file
+pub mod keepalive;
+pub mod pool;
+pub mod reconnect;
+pub mod server;
+pub mod snapshot;
+#[cfg(test)]
+mod tests;
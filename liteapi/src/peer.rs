@@ -61,6 +61,22 @@ impl<T> Stream for LitePeer<T> where T: Stream<Item = Result<Bytes, AdnlError>>
     }
 }
 
+impl<T> From<tokio_tower::Error<LitePeer<T>, Message>> for LiteError
+where
+    LitePeer<T>: Sink<Message, Error = LiteError> + Stream<Item = Result<Message, LiteError>>,
+{
+    fn from(error: tokio_tower::Error<LitePeer<T>, Message>) -> Self {
+        match error {
+            tokio_tower::Error::BrokenTransportSend(e) => e,
+            tokio_tower::Error::BrokenTransportRecv(Some(e)) => e,
+            tokio_tower::Error::BrokenTransportRecv(None) => LiteError::AdnlError(AdnlError::EndOfStream),
+            // the background task of multiplex client is gone, so the connection is dead
+            tokio_tower::Error::ClientDropped | tokio_tower::Error::TransportFull => LiteError::AdnlError(AdnlError::EndOfStream),
+            tokio_tower::Error::Desynchronized => LiteError::UnexpectedMessage,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum LiteTag {
     Int256(Int256),
@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::future::{join_all, BoxFuture};
+use tokio::net::ToSocketAddrs;
+use tokio::time::Instant;
+use tower::{Service, ServiceExt as _};
+
+use crate::client::{connect_buffered, BufferedService};
+use crate::tl::request::WrappedRequest;
+use crate::tl::response::Response;
+use crate::types::LiteError;
+
+type Result<T> = std::result::Result<T, LiteError>;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+const COOLDOWN: Duration = Duration::from_secs(30);
+
+struct Backend {
+    service: BufferedService,
+    /// Until when the liteserver is excluded from the rotation after a failure
+    down_until: Mutex<Option<Instant>>,
+}
+
+impl Backend {
+    fn new(service: BufferedService) -> Self {
+        Self { service, down_until: Mutex::new(None) }
+    }
+
+    fn is_up(&self, now: Instant) -> bool {
+        self.down_until.lock().unwrap().is_none_or(|until| until <= now)
+    }
+}
+
+/// Lite service spreading queries across several liteservers in round-robin order.
+///
+/// A liteserver which fails with a transport error or does not answer within the query timeout
+/// is excluded from the rotation for the cooldown period, and the query is retried on the next
+/// one. After the cooldown the liteserver gets queries again, and is excluded once more if it
+/// still fails. When every liteserver is cooling down, all of them are tried anyway.
+#[derive(Clone)]
+pub struct LitePool {
+    backends: Arc<Vec<Backend>>,
+    next: Arc<AtomicUsize>,
+    query_timeout: Duration,
+    cooldown: Duration,
+}
+
+impl LitePool {
+    /// Connects to every given liteserver, skipping the unreachable ones
+    pub async fn connect<A, K>(servers: impl IntoIterator<Item = (A, K)>) -> Result<Self>
+    where
+        A: ToSocketAddrs,
+        K: AsRef<[u8]>,
+    {
+        let connections = servers.into_iter().map(|(address, public_key)| connect_buffered(address, public_key, None));
+        let backends: Vec<_> = join_all(connections)
+            .await
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, connection)| match connection {
+                Ok(service) => Some(service),
+                Err(e) => {
+                    log::warn!("Can't connect to liteserver #{}: {:?}", i, e);
+                    None
+                }
+            })
+            .collect();
+        if backends.is_empty() {
+            return Err(LiteError::NoAvailableServers);
+        }
+        log::debug!("Connected to {} liteservers", backends.len());
+        Ok(Self::from_services(backends))
+    }
+
+    pub(crate) fn from_services(services: Vec<BufferedService>) -> Self {
+        Self {
+            backends: Arc::new(services.into_iter().map(Backend::new).collect()),
+            next: Arc::new(AtomicUsize::new(0)),
+            query_timeout: QUERY_TIMEOUT,
+            cooldown: COOLDOWN,
+        }
+    }
+
+    /// Sets how long to wait for an answer before failing over to another liteserver
+    pub fn query_timeout(mut self, timeout: Duration) -> Self {
+        self.query_timeout = timeout;
+        self
+    }
+
+    /// Sets how long a failed liteserver is excluded from the rotation
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Number of liteservers currently in the rotation
+    pub fn alive_count(&self) -> usize {
+        let now = Instant::now();
+        self.backends.iter().filter(|b| b.is_up(now)).count()
+    }
+}
+
+impl Service<WrappedRequest> for LitePool {
+    type Response = Response;
+    type Error = LiteError;
+    type Future = BoxFuture<'static, Result<Response>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: WrappedRequest) -> Self::Future {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let backends = self.backends.clone();
+        let query_timeout = self.query_timeout;
+        let cooldown = self.cooldown;
+        Box::pin(async move {
+            let now = Instant::now();
+            let all_down = backends.iter().all(|b| !b.is_up(now));
+            let mut last_error = LiteError::NoAvailableServers;
+            for offset in 0..backends.len() {
+                let index = (start + offset) % backends.len();
+                let backend = &backends[index];
+                if !all_down && !backend.is_up(now) {
+                    continue;
+                }
+                let mut service = backend.service.clone();
+                let request = request.clone();
+                let result = tokio::time::timeout(query_timeout, async move {
+                    service.ready().await?.call(request).await
+                }).await;
+                let error = match result {
+                    Ok(Ok(response)) => {
+                        *backend.down_until.lock().unwrap() = None;
+                        return Ok(response);
+                    }
+                    Ok(Err(e)) => LiteError::from_boxed(e),
+                    Err(_) => LiteError::Timeout,
+                };
+                if !error.is_transport() {
+                    return Err(error);
+                }
+                log::warn!("Liteserver #{} failed, excluding it from the pool for {:?}: {:?}", index, cooldown, error);
+                *backend.down_until.lock().unwrap() = Some(Instant::now() + cooldown);
+                last_error = error;
+            }
+            Err(last_error)
+        })
+    }
+}
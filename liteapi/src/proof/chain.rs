@@ -0,0 +1,129 @@
+use crate::block::BlockchainConfig;
+use crate::boc::{dict_get, Cell};
+use crate::tl::common::{BlockIdExt, BlockLink};
+use crate::tl::response::PartialBlockProof;
+
+use super::*;
+
+/// Config param with the current validator set
+const CONFIG_CURRENT_VALIDATORS: u32 = 34;
+
+/// Checks links of `liteServer.partialBlockProof` starting from the `trusted` masterchain block,
+/// which is a block known to the client, the zerostate or the init block of the network config.
+///
+/// Returns the last block proven by the links, it is the target block if the proof is complete.
+pub fn check_partial_block_proof(trusted: &BlockIdExt, proof: &PartialBlockProof) -> Result<BlockIdExt> {
+    if proof.from != *trusted {
+        return Err(ProofError::InvalidChain("proof does not start from the trusted block"));
+    }
+    let mut current = trusted.clone();
+    for link in &proof.steps {
+        current = check_link(&current, link)?;
+    }
+    if current != proof.to {
+        return Err(ProofError::InvalidChain("links do not end at the destination block"));
+    }
+    Ok(current)
+}
+
+/// Checks that the link starts from `current` block and proves its destination block
+fn check_link(current: &BlockIdExt, link: &BlockLink) -> Result<BlockIdExt> {
+    let (forward, to_key_block, from, to, dest_proof) = match link {
+        BlockLink::BlockLinkBack { to_key_block, from, to, dest_proof, .. } => (false, *to_key_block, from, to, dest_proof),
+        BlockLink::BlockLinkForward { to_key_block, from, to, dest_proof, .. } => (true, *to_key_block, from, to, dest_proof),
+    };
+    if from != current {
+        return Err(ProofError::InvalidChain("link does not start where the previous one ends"));
+    }
+    if from.workchain != -1 || to.workchain != -1 {
+        return Err(ProofError::InvalidChain("link does not connect masterchain blocks"));
+    }
+    if forward != (from.seqno < to.seqno) {
+        return Err(ProofError::InvalidChain("link direction does not match block seqnos"));
+    }
+    // the zerostate has no block header
+    if to.seqno != 0 || !dest_proof.is_empty() {
+        let root = virtualize(&Cell::from_boc(dest_proof)?)?;
+        if root.hash(0) != to.root_hash.0 {
+            return Err(ProofError::BlockHashMismatch);
+        }
+        if is_key_block(&root)? != to_key_block {
+            return Err(ProofError::KeyBlockMismatch);
+        }
+    }
+
+    match link {
+        BlockLink::BlockLinkBack { proof, state_proof, .. } => {
+            let state_hash = check_block_header_proof(&Cell::from_boc(proof)?, from)?;
+            let state = virtualize(&Cell::from_boc(state_proof)?)?;
+            if state.hash(0) != state_hash {
+                return Err(ProofError::StateHashMismatch);
+            }
+            check_prev_block(&McStateExtra::parse(&ShardState::parse(&state)?)?, to, to_key_block)?;
+        }
+        BlockLink::BlockLinkForward { config_proof, signatures, .. } => {
+            let root = virtualize(&Cell::from_boc(config_proof)?)?;
+            if root.hash(0) != from.root_hash.0 {
+                return Err(ProofError::BlockHashMismatch);
+            }
+            // root hash of the zerostate is the hash of its state
+            let config = if from.seqno == 0 {
+                McStateExtra::parse(&ShardState::parse(&root)?)?.config
+            } else {
+                key_block_config(&root)?
+            };
+            let config = BlockchainConfig::new(config);
+            let validators = config
+                .validator_set(CONFIG_CURRENT_VALIDATORS)?
+                .ok_or(ProofError::Malformed("missing current validator set"))?;
+            let validators = match config.catchain_config()? {
+                Some(catchain) => validators.masterchain_validators(&catchain, signatures.catchain_seqno),
+                None => validators.masterchain().to_vec(),
+            };
+            // validator set hash of the signature set is not checked, signatures are matched to
+            // validators by their ids, so only the selected validators matter and not their order
+            let check = check_signatures(&validators, to, signatures)?;
+            if !check.is_enough() {
+                return Err(ProofError::NotEnoughSignatures { signed: check.signed_weight, total: check.total_weight });
+            }
+        }
+    }
+    Ok(to.clone())
+}
+
+/// Checks that `block` is in the list of previous blocks of masterchain state
+fn check_prev_block(extra: &McStateExtra, block: &BlockIdExt, key_block: bool) -> Result<()> {
+    // flags:(## 16) validator_info:ValidatorInfo prev_blocks:OldMcBlocksInfo
+    let mut info = extra.info.parse()?;
+    info.skip_bits(16 + 65)?;
+    let prev_blocks = load_maybe_reference(&mut info)?.ok_or(ProofError::PrevBlockMismatch)?;
+    let mut leaf = dict_get(&prev_blocks, &block.seqno.to_be_bytes(), 32)?.ok_or(ProofError::PrevBlockMismatch)?;
+    // extra key:Bool max_end_lt:uint64, value key:Bool end_lt:uint64 seq_no:uint32 root_hash file_hash
+    leaf.skip_bits(65)?;
+    let is_key = leaf.load_bit()?;
+    leaf.skip_bits(64)?;
+    let seqno = leaf.load_uint(32)? as u32;
+    let root_hash = leaf.load_bytes(32)?;
+    let file_hash = leaf.load_bytes(32)?;
+    if seqno != block.seqno || root_hash != block.root_hash.0 || file_hash != block.file_hash.0 {
+        return Err(ProofError::PrevBlockMismatch);
+    }
+    if is_key != key_block {
+        return Err(ProofError::KeyBlockMismatch);
+    }
+    Ok(())
+}
+
+fn is_key_block(root: &Cell) -> Result<bool> {
+    let mut block = root.parse()?;
+    if block.load_uint(32)? != BLOCK_TAG {
+        return Err(ProofError::Malformed("invalid block tag"));
+    }
+    let mut info = block.load_reference()?.parse()?;
+    if info.load_uint(32)? != BLOCK_INFO_TAG {
+        return Err(ProofError::Malformed("invalid block info tag"));
+    }
+    // version, not_master, after_merge, before_split, after_split, want_split, want_merge
+    info.skip_bits(32 + 6)?;
+    Ok(info.load_bit()?)
+}
@@ -0,0 +1,353 @@
+//! Verification of Merkle proofs returned by liteservers
+mod chain;
+#[cfg(test)]
+mod tests;
+mod validators;
+
+use thiserror::Error;
+
+use crate::block::{ShardDescr, ShardIdent};
+use crate::boc::{deserialize, dict_get, BocError, Cell, CellSlice, CellType};
+use crate::tl::common::{AccountId, BlockIdExt, Int256};
+use crate::tl::response::{AccountState, ConfigInfo, RunMethodResult};
+
+pub use chain::check_partial_block_proof;
+pub use validators::{block_signature_payload, check_signatures, SignatureCheck};
+/// Validator sets are decoded with the rest of the config, the types are defined in [`crate::block`]
+pub use crate::block::{Validator, ValidatorSet};
+
+type Result<T> = std::result::Result<T, ProofError>;
+
+const BLOCK_TAG: u64 = 0x11ef55aa;
+const BLOCK_INFO_TAG: u64 = 0x9bc7a987;
+const BLOCK_EXTRA_TAG: u64 = 0x4a33f6fd;
+const SHARD_STATE_TAG: u64 = 0x9023afe2;
+const MC_STATE_EXTRA_TAG: u64 = 0xcc26;
+const MC_BLOCK_EXTRA_TAG: u64 = 0xcca5;
+
+#[derive(Debug, Error)]
+pub enum ProofError {
+    #[error("Malformed proof")]
+    Boc(#[from] BocError),
+    #[error("Malformed proof: {0}")]
+    Malformed(&'static str),
+    #[error("Liteserver answered for another block")]
+    UnexpectedBlock,
+    #[error("Block proof does not match block root hash")]
+    BlockHashMismatch,
+    #[error("State proof does not match state hash of the block")]
+    StateHashMismatch,
+    #[error("Shard block is not the top block of its shard in masterchain state")]
+    ShardBlockMismatch,
+    #[error("Account state does not match the state proof")]
+    AccountMismatch,
+    #[error("Invalid block proof chain: {0}")]
+    InvalidChain(&'static str),
+    #[error("Block is not in the list of previous blocks of masterchain state")]
+    PrevBlockMismatch,
+    #[error("Key block flag does not match the block header")]
+    KeyBlockMismatch,
+    #[error("Block is not a key block, validator set is unknown")]
+    NotKeyBlock,
+    #[error("Signature of unknown validator {0:?}")]
+    UnknownValidator(Int256),
+    #[error("Duplicate signature of validator {0:?}")]
+    DuplicateSignature(Int256),
+    #[error("Invalid signature of validator {0:?}")]
+    InvalidSignature(Int256),
+    #[error("Block is signed by {signed} of {total} validator weight, more than 2/3 is required")]
+    NotEnoughSignatures { signed: u64, total: u64 },
+}
+
+/// Account state checked against Merkle proofs
+#[derive(Debug, Clone)]
+pub struct VerifiedAccountState {
+    /// Shard block the state is taken from
+    pub shard_block: BlockIdExt,
+    /// Root cell of `Account`, `None` if the account does not exist
+    pub account: Option<Cell>,
+    pub last_trans_lt: u64,
+    pub last_trans_hash: Int256,
+    /// Time of the shard state
+    pub gen_utime: u32,
+    /// Logical time of the shard state
+    pub gen_lt: u64,
+}
+
+/// Checks `liteServer.accountState` returned for the `requested` block.
+///
+/// The shard block is checked to be in the masterchain state of the requested block, and the
+/// account to be in the shard state of the shard block.
+pub fn check_account_state(requested: &BlockIdExt, account: &AccountId, response: &AccountState) -> Result<VerifiedAccountState> {
+    if response.id != *requested {
+        return Err(ProofError::UnexpectedBlock);
+    }
+    check_shard_proof(&response.id, &response.shardblk, &response.shard_proof)?;
+    let state = if response.state.is_empty() { None } else { Some(Cell::from_boc(&response.state)?) };
+    check_account_proof(&response.shardblk, account, &response.proof, state)
+}
+
+/// Checks `liteServer.runMethodResult` returned for the `requested` block, queried with
+/// proofs and state proof.
+///
+/// The account cells used by the get-method are checked to be in the state of the account after
+/// the shard block. The result itself is computed by liteserver and is not checked.
+pub fn check_run_method_result(requested: &BlockIdExt, account: &AccountId, response: &RunMethodResult) -> Result<VerifiedAccountState> {
+    if response.id != *requested {
+        return Err(ProofError::UnexpectedBlock);
+    }
+    let (Some(shard_proof), Some(proof), Some(state_proof)) = (&response.shard_proof, &response.proof, &response.state_proof) else {
+        return Err(ProofError::Malformed("run method result without proofs"));
+    };
+    check_shard_proof(&response.id, &response.shardblk, shard_proof)?;
+    let state = virtualize(&Cell::from_boc(state_proof)?)?;
+    check_account_proof(&response.shardblk, account, proof, Some(state))
+}
+
+/// Checks that `shard_block` is the top block of its shard in the state of `mc_block`.
+///
+/// The proof is empty when both blocks are the same.
+pub fn check_shard_proof(mc_block: &BlockIdExt, shard_block: &BlockIdExt, shard_proof: &[u8]) -> Result<()> {
+    if mc_block == shard_block {
+        return Ok(());
+    }
+    if mc_block.workchain != -1 {
+        return Err(ProofError::Malformed("shard proof from non-masterchain block"));
+    }
+    let roots = deserialize(shard_proof)?;
+    if roots.len() != 2 {
+        return Err(ProofError::Malformed("shard proof must have two roots"));
+    }
+    let state_hash = check_block_header_proof(&roots[0], mc_block)?;
+    let state = virtualize(&roots[1])?;
+    if state.hash(0) != state_hash {
+        return Err(ProofError::StateHashMismatch);
+    }
+
+    let extra = McStateExtra::parse(&ShardState::parse(&state)?)?;
+    let shard_hashes = extra.shard_hashes.ok_or(ProofError::ShardBlockMismatch)?;
+    let mut value = dict_get(&shard_hashes, &shard_block.workchain.to_be_bytes(), 32)?.ok_or(ProofError::ShardBlockMismatch)?;
+    let mut node = value.load_reference()?.parse()?;
+
+    // the path to the shard in the binary tree is the shard prefix, other branches are pruned
+    let shard = ShardIdent::of_block(shard_block).ok_or(ProofError::Malformed("invalid shard id"))?;
+    for i in 0..shard.prefix_len() {
+        if !node.load_bit()? {
+            return Err(ProofError::ShardBlockMismatch);
+        }
+        let left = node.load_reference()?;
+        let right = node.load_reference()?;
+        node = if shard_block.shard & (1 << (63 - i)) != 0 { right } else { left }.parse()?;
+    }
+    if node.load_bit()? {
+        return Err(ProofError::ShardBlockMismatch);
+    }
+    if ShardDescr::parse(&mut node)?.block_id(&shard) != *shard_block {
+        return Err(ProofError::ShardBlockMismatch);
+    }
+    Ok(())
+}
+
+/// Checks that `state` is the state of `account` after `shard_block`, `None` meaning the account
+/// does not exist
+pub fn check_account_proof(shard_block: &BlockIdExt, account: &AccountId, proof: &[u8], state: Option<Cell>) -> Result<VerifiedAccountState> {
+    if account.workchain != shard_block.workchain {
+        return Err(ProofError::Malformed("account is not in the workchain of the shard block"));
+    }
+    let roots = deserialize(proof)?;
+    if roots.len() != 2 {
+        return Err(ProofError::Malformed("account proof must have two roots"));
+    }
+    let state_hash = check_block_header_proof(&roots[0], shard_block)?;
+    let shard_state = virtualize(&roots[1])?;
+    if shard_state.hash(0) != state_hash {
+        return Err(ProofError::StateHashMismatch);
+    }
+    let shard_state = ShardState::parse(&shard_state)?;
+
+    let mut verified = VerifiedAccountState {
+        shard_block: shard_block.clone(),
+        account: None,
+        last_trans_lt: 0,
+        last_trans_hash: Int256([0; 32]),
+        gen_utime: shard_state.gen_utime,
+        gen_lt: shard_state.gen_lt,
+    };
+    let leaf = match &shard_state.accounts {
+        Some(accounts) => dict_get(accounts, &account.id.0, 256)?,
+        None => None,
+    };
+    match (leaf, state) {
+        (Some(mut leaf), Some(state)) => {
+            // depth_balance$_ split_depth:(#<= 30) balance:CurrencyCollection
+            leaf.skip_bits(5)?;
+            leaf.load_coins()?;
+            load_maybe_reference(&mut leaf)?;
+            // account_descr$_ account:^Account last_trans_hash:bits256 last_trans_lt:uint64
+            let account_root = leaf.load_reference()?;
+            if account_root.hash(0) != state.hash(0) {
+                return Err(ProofError::AccountMismatch);
+            }
+            verified.last_trans_hash = Int256(leaf.load_bytes(32)?.try_into().unwrap());
+            verified.last_trans_lt = leaf.load_uint(64)?;
+            verified.account = Some(state);
+        }
+        (None, None) => {}
+        _ => return Err(ProofError::AccountMismatch),
+    }
+    Ok(verified)
+}
+
+/// Checks `liteServer.configInfo` returned for the `requested` block and returns the root of
+/// config dictionary.
+///
+/// The config is taken from the masterchain state of the block, or from the block itself if it
+/// was requested with `extract_from_key_block`.
+pub fn check_config_info(requested: &BlockIdExt, response: &ConfigInfo) -> Result<Cell> {
+    if response.id != *requested {
+        return Err(ProofError::UnexpectedBlock);
+    }
+    if response.id.workchain != -1 {
+        return Err(ProofError::Malformed("config from non-masterchain block"));
+    }
+    let root = virtualize(&Cell::from_boc(&response.config_proof)?)?;
+    if response.extract_from_key_block.is_some() {
+        if root.hash(0) != response.id.root_hash.0 {
+            return Err(ProofError::BlockHashMismatch);
+        }
+        return key_block_config(&root);
+    }
+    let state_hash = check_block_header_proof(&Cell::from_boc(&response.state_proof)?, &response.id)?;
+    if root.hash(0) != state_hash {
+        return Err(ProofError::StateHashMismatch);
+    }
+    Ok(McStateExtra::parse(&ShardState::parse(&root)?)?.config)
+}
+
+/// Root of the tree contained in Merkle proof
+fn virtualize(proof: &Cell) -> Result<Cell> {
+    if proof.cell_type() != CellType::MerkleProof {
+        return Err(ProofError::Malformed("expected Merkle proof cell"));
+    }
+    Ok(proof.references()[0].clone())
+}
+
+/// Checks Merkle proof of the block header and returns hash of the state after the block
+fn check_block_header_proof(proof: &Cell, block: &BlockIdExt) -> Result<[u8; 32]> {
+    let root = virtualize(proof)?;
+    if root.hash(0) != block.root_hash.0 {
+        return Err(ProofError::BlockHashMismatch);
+    }
+    // block#11ef55aa global_id:int32 info:^BlockInfo value_flow:^ValueFlow
+    //     state_update:^(MERKLE_UPDATE ShardState) extra:^BlockExtra = Block;
+    let mut slice = root.parse()?;
+    if slice.load_uint(32)? != BLOCK_TAG {
+        return Err(ProofError::Malformed("invalid block tag"));
+    }
+    slice.load_reference()?;
+    slice.load_reference()?;
+    let state_update = slice.load_reference()?;
+    if state_update.cell_type() != CellType::MerkleUpdate {
+        return Err(ProofError::Malformed("block state update is not a Merkle update"));
+    }
+    // the update keeps hashes of old and new states right after the tag
+    Ok(state_update.data()[33..65].try_into().unwrap())
+}
+
+fn load_maybe_reference(slice: &mut CellSlice) -> Result<Option<Cell>> {
+    Ok(if slice.load_bit()? { Some(slice.load_reference()?) } else { None })
+}
+
+/// Fields of `ShardStateUnsplit` needed to check proofs
+struct ShardState {
+    gen_utime: u32,
+    gen_lt: u64,
+    accounts: Option<Cell>,
+    custom: Option<Cell>,
+}
+
+impl ShardState {
+    fn parse(root: &Cell) -> Result<Self> {
+        let mut slice = root.parse()?;
+        if slice.load_uint(32)? != SHARD_STATE_TAG {
+            return Err(ProofError::Malformed("invalid shard state tag"));
+        }
+        // global_id, shard_id, seq_no, vert_seq_no
+        slice.skip_bits(32 + 104 + 32 + 32)?;
+        let gen_utime = slice.load_uint(32)? as u32;
+        let gen_lt = slice.load_uint(64)?;
+        // min_ref_mc_seqno, before_split
+        slice.skip_bits(32 + 1)?;
+        slice.load_reference()?;
+        // ShardAccounts is HashmapAugE, its root is optional
+        let mut accounts = slice.load_reference()?.parse()?;
+        let accounts = load_maybe_reference(&mut accounts)?;
+        slice.load_reference()?;
+        let custom = load_maybe_reference(&mut slice)?;
+        Ok(Self { gen_utime, gen_lt, accounts, custom })
+    }
+}
+
+/// Fields of `McStateExtra` needed to check proofs
+struct McStateExtra {
+    shard_hashes: Option<Cell>,
+    /// Root of `Hashmap 32 ^Cell` with config params
+    config: Cell,
+    /// Cell with validator info and previous blocks
+    info: Cell,
+}
+
+impl McStateExtra {
+    fn parse(state: &ShardState) -> Result<Self> {
+        let custom = state.custom.as_ref().ok_or(ProofError::Malformed("masterchain state without extra"))?;
+        let mut slice = custom.parse()?;
+        if slice.load_uint(16)? != MC_STATE_EXTRA_TAG {
+            return Err(ProofError::Malformed("invalid masterchain state extra tag"));
+        }
+        let shard_hashes = load_maybe_reference(&mut slice)?;
+        // _ config_addr:bits256 config:^(Hashmap 32 ^Cell) = ConfigParams;
+        slice.skip_bits(256)?;
+        let config = slice.load_reference()?;
+        let info = slice.load_reference()?;
+        Ok(Self { shard_hashes, config, info })
+    }
+}
+
+/// Root of config dictionary kept in the extra of key block
+fn key_block_config(root: &Cell) -> Result<Cell> {
+    let mut block = root.parse()?;
+    if block.load_uint(32)? != BLOCK_TAG {
+        return Err(ProofError::Malformed("invalid block tag"));
+    }
+    // info, value_flow, state_update
+    for _ in 0..3 {
+        block.load_reference()?;
+    }
+    let mut extra = block.load_reference()?.parse()?;
+    if extra.load_uint(32)? != BLOCK_EXTRA_TAG {
+        return Err(ProofError::Malformed("invalid block extra tag"));
+    }
+    // in_msg_descr, out_msg_descr, account_blocks, rand_seed, created_by
+    for _ in 0..3 {
+        extra.load_reference()?;
+    }
+    extra.skip_bits(256 + 256)?;
+    let mut custom = load_maybe_reference(&mut extra)?.ok_or(ProofError::NotKeyBlock)?.parse()?;
+    if custom.load_uint(16)? != MC_BLOCK_EXTRA_TAG {
+        return Err(ProofError::Malformed("invalid masterchain block extra tag"));
+    }
+    if !custom.load_bit()? {
+        return Err(ProofError::NotKeyBlock);
+    }
+    // shard_hashes, shard_fees with ShardFeeCreated extra of two CurrencyCollection
+    load_maybe_reference(&mut custom)?;
+    load_maybe_reference(&mut custom)?;
+    for _ in 0..2 {
+        custom.load_coins()?;
+        load_maybe_reference(&mut custom)?;
+    }
+    custom.load_reference()?;
+    // _ config_addr:bits256 config:^(Hashmap 32 ^Cell) = ConfigParams;
+    custom.skip_bits(256)?;
+    Ok(custom.load_reference()?)
+}
@@ -0,0 +1,453 @@
+use std::error::Error;
+
+use everscale_crypto::ed25519;
+use sha2::{Digest, Sha256};
+
+use crate::block::BlockchainConfig;
+use crate::boc::{key_bits, make_dict, BocWriter, CellBuilder};
+use crate::tl::common::{AccountId, BlockIdExt, BlockLink, Int256, Signature, SignatureSet};
+use crate::tl::response::{AccountState, ConfigInfo, PartialBlockProof, RunMethodResult};
+
+use super::*;
+
+type TestResult = std::result::Result<(), Box<dyn Error>>;
+
+const SHARD_FULL: u64 = 1 << 63;
+
+fn merkle_proof(cell: &Cell) -> Cell {
+    let mut data = vec![3];
+    data.extend_from_slice(&cell.hash(0));
+    data.extend_from_slice(&cell.depth(0).to_be_bytes());
+    Cell::new(CellType::MerkleProof, data, 8 + 256 + 16, vec![cell.clone()]).unwrap()
+}
+
+/// Pruned branch of a cell with level 0, `level` is the number of enclosing Merkle cells
+fn pruned(cell: &Cell, level: u8) -> Cell {
+    let mut data = vec![1, 1 << (level - 1)];
+    data.extend_from_slice(&cell.hash(0));
+    data.extend_from_slice(&cell.depth(0).to_be_bytes());
+    Cell::new(CellType::PrunedBranch, data, 8 * (2 + 32 + 2), vec![]).unwrap()
+}
+
+fn empty() -> Cell {
+    CellBuilder::new().build().unwrap()
+}
+
+/// `ShardAccounts` with a single account
+fn shard_accounts(id: &[u8; 32], account: &Cell, last_trans_lt: u64) -> std::result::Result<Cell, BocError> {
+    let leaf = CellBuilder::new()
+        // hml_long$10 with 9-bit length
+        .store_uint(0b10, 2)?
+        .store_uint(256, 9)?
+        .store_bytes(id)?
+        // depth_balance$_ split_depth:0 balance:0 without extra currencies
+        .store_uint(0, 5)?
+        .store_coins(0)?
+        .store_bit(false)?
+        .store_reference(account.clone())?
+        .store_bytes(&[7; 32])?
+        .store_uint(last_trans_lt, 64)?
+        .build()?;
+    CellBuilder::new().store_bit(true)?.store_reference(leaf)?.store_uint(0, 5)?.store_coins(0)?.store_bit(false)?.build()
+}
+
+fn shard_state(workchain: i32, accounts: Cell, custom: Option<Cell>) -> std::result::Result<Cell, BocError> {
+    let mut builder = CellBuilder::new();
+    builder
+        .store_uint(SHARD_STATE_TAG, 32)?
+        .store_int(-239, 32)?
+        .store_uint(0, 8)?
+        .store_int(workchain as i64, 32)?
+        .store_uint(SHARD_FULL, 64)?
+        .store_uint(100, 32)?
+        .store_uint(0, 32)?
+        .store_uint(1_700_000_000, 32)?
+        .store_uint(5000, 64)?
+        .store_uint(0, 32)?
+        .store_bit(false)?
+        .store_reference(empty())?
+        .store_reference(accounts)?
+        .store_reference(empty())?
+        .store_bit(custom.is_some())?;
+    if let Some(custom) = custom {
+        builder.store_reference(custom)?;
+    }
+    builder.build()
+}
+
+fn merkle_update(old: &Cell, new: &Cell) -> std::result::Result<Cell, BocError> {
+    let mut data = vec![4];
+    data.extend_from_slice(&old.hash(0));
+    data.extend_from_slice(&new.hash(0));
+    data.extend_from_slice(&old.depth(0).to_be_bytes());
+    data.extend_from_slice(&new.depth(0).to_be_bytes());
+    Cell::new(CellType::MerkleUpdate, data, 8 + 512 + 32, vec![old.clone(), new.clone()])
+}
+
+fn block_info(seqno: u32, key_block: bool) -> std::result::Result<Cell, BocError> {
+    CellBuilder::new()
+        .store_uint(BLOCK_INFO_TAG, 32)?
+        .store_uint(0, 32)?
+        .store_uint(0, 6)?
+        .store_bit(key_block)?
+        .store_uint(0, 9)?
+        .store_uint(seqno as u64, 32)?
+        .build()
+}
+
+fn block_cell(info: Cell, state_update: Cell, extra: Cell) -> std::result::Result<Cell, BocError> {
+    CellBuilder::new()
+        .store_uint(BLOCK_TAG, 32)?
+        .store_int(-239, 32)?
+        .store_reference(info)?
+        .store_reference(empty())?
+        .store_reference(state_update)?
+        .store_reference(extra)?
+        .build()
+}
+
+/// Block with the given new state and its proof with other fields pruned
+fn make_block(state: &Cell) -> std::result::Result<(Cell, Cell), BocError> {
+    let update = merkle_update(&empty(), state)?;
+    let info = block_info(100, false)?;
+    let full = block_cell(info.clone(), update.clone(), empty())?;
+    let update_proof = Cell::new(
+        CellType::MerkleUpdate,
+        update.data().to_vec(),
+        update.bit_len(),
+        vec![pruned(&update.references()[0], 2), pruned(state, 2)],
+    )?;
+    let partial = CellBuilder::new()
+        .store_uint(BLOCK_TAG, 32)?
+        .store_int(-239, 32)?
+        .store_reference(pruned(&info, 1))?
+        .store_reference(pruned(&empty(), 1))?
+        .store_reference(update_proof)?
+        .store_reference(pruned(&empty(), 1))?
+        .build()?;
+    Ok((full, partial))
+}
+
+fn block_id(workchain: i32, block: &Cell) -> BlockIdExt {
+    BlockIdExt { workchain, shard: SHARD_FULL, seqno: 100, root_hash: Int256(block.repr_hash()), file_hash: Int256([1; 32]) }
+}
+
+fn account_state(id: BlockIdExt, shardblk: BlockIdExt, shard_proof: Vec<u8>, proof: Vec<u8>, account: &Cell) -> AccountState {
+    AccountState { id, shardblk, shard_proof, proof, state: account.to_boc() }
+}
+
+#[test]
+fn test_masterchain_account() -> TestResult {
+    let account = CellBuilder::new().store_bytes(b"account")?.build()?;
+    let address = AccountId { workchain: -1, id: Int256([5; 32]) };
+    let state = shard_state(-1, shard_accounts(&address.id.0, &account, 42)?, None)?;
+    let (block, block_partial) = make_block(&state)?;
+    let id = block_id(-1, &block);
+    let proof = BocWriter::new(vec![merkle_proof(&block_partial), merkle_proof(&state)]).to_bytes();
+    let response = account_state(id.clone(), id.clone(), vec![], proof.clone(), &account);
+
+    let verified = check_account_state(&id, &address, &response)?;
+    assert_eq!(verified.account, Some(account));
+    assert_eq!(verified.last_trans_lt, 42);
+    assert_eq!(verified.last_trans_hash, Int256([7; 32]));
+    assert_eq!(verified.gen_utime, 1_700_000_000);
+
+    let forged = account_state(id.clone(), id.clone(), vec![], proof.clone(), &empty());
+    assert!(matches!(check_account_state(&id, &address, &forged), Err(ProofError::AccountMismatch)));
+
+    let missing = AccountId { workchain: -1, id: Int256([6; 32]) };
+    assert!(matches!(check_account_state(&id, &missing, &response), Err(ProofError::AccountMismatch)));
+    let mut response_missing = response.clone();
+    response_missing.state = vec![];
+    assert!(check_account_state(&id, &missing, &response_missing)?.account.is_none());
+
+    let mut other = id.clone();
+    other.root_hash = Int256([0; 32]);
+    let response = account_state(other.clone(), other.clone(), vec![], proof, &empty());
+    assert!(matches!(check_account_state(&other, &address, &response), Err(ProofError::BlockHashMismatch)));
+    Ok(())
+}
+
+#[test]
+fn test_run_method_result() -> TestResult {
+    let data = CellBuilder::new().store_bytes(b"data")?.build()?;
+    let account = CellBuilder::new().store_bytes(b"account")?.store_reference(data.clone())?.build()?;
+    let address = AccountId { workchain: -1, id: Int256([5; 32]) };
+    let state = shard_state(-1, shard_accounts(&address.id.0, &account, 42)?, None)?;
+    let (block, block_partial) = make_block(&state)?;
+    let id = block_id(-1, &block);
+    let proof = BocWriter::new(vec![merkle_proof(&block_partial), merkle_proof(&state)]).to_bytes();
+    // the get-method did not load the data cell
+    let used = CellBuilder::new().store_bytes(b"account")?.store_reference(pruned(&data, 1))?.build()?;
+    let response = RunMethodResult {
+        mode: (),
+        id: id.clone(),
+        shardblk: id.clone(),
+        shard_proof: Some(vec![]),
+        proof: Some(proof),
+        state_proof: Some(merkle_proof(&used).to_boc()),
+        init_c7: None,
+        lib_extras: None,
+        exit_code: 0,
+        result: None,
+    };
+
+    let verified = check_run_method_result(&id, &address, &response)?;
+    assert_eq!(verified.account, Some(used));
+    assert_eq!(verified.last_trans_lt, 42);
+
+    let forged = RunMethodResult { state_proof: Some(merkle_proof(&empty()).to_boc()), ..response.clone() };
+    assert!(matches!(check_run_method_result(&id, &address, &forged), Err(ProofError::AccountMismatch)));
+    let without_proofs = RunMethodResult { shard_proof: None, proof: None, ..response };
+    assert!(matches!(check_run_method_result(&id, &address, &without_proofs), Err(ProofError::Malformed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_shard_account() -> TestResult {
+    let account = CellBuilder::new().store_bytes(b"account")?.build()?;
+    let address = AccountId { workchain: 0, id: Int256([5; 32]) };
+    let state = shard_state(0, shard_accounts(&address.id.0, &account, 42)?, None)?;
+    let (block, block_partial) = make_block(&state)?;
+    let shard_id = block_id(0, &block);
+
+    // bt_leaf$0 shard_descr#b seq_no reg_mc_seqno start_lt end_lt root_hash file_hash flags
+    //     next_catchain_seqno next_validator_shard min_ref_mc_seqno gen_utime, the rest is omitted
+    let descr = CellBuilder::new()
+        .store_bit(false)?
+        .store_uint(0xb, 4)?
+        .store_uint(100, 32)?
+        .store_uint(0, 32)?
+        .store_uint(0, 64)?
+        .store_uint(0, 64)?
+        .store_bytes(&shard_id.root_hash.0)?
+        .store_bytes(&shard_id.file_hash.0)?
+        .store_uint(0, 8)?
+        .store_uint(0, 32)?
+        .store_uint(0, 64)?
+        .store_uint(0, 64)?
+        .build()?;
+    // hml_same$11 v:0 n:32 with a single key for workchain 0
+    let shard_hashes = CellBuilder::new().store_uint(0b110, 3)?.store_uint(32, 6)?.store_reference(descr)?.build()?;
+    let extra = CellBuilder::new()
+        .store_uint(MC_STATE_EXTRA_TAG, 16)?
+        .store_bit(true)?
+        .store_reference(shard_hashes)?
+        .store_bytes(&[0; 32])?
+        .store_reference(empty())?
+        .store_reference(empty())?
+        .build()?;
+    let mc_state = shard_state(-1, CellBuilder::new().store_bit(false)?.store_uint(0, 10)?.build()?, Some(extra))?;
+    let (mc_block, mc_block_partial) = make_block(&mc_state)?;
+    let mc_id = block_id(-1, &mc_block);
+
+    let shard_proof = BocWriter::new(vec![merkle_proof(&mc_block_partial), merkle_proof(&mc_state)]).to_bytes();
+    let proof = BocWriter::new(vec![merkle_proof(&block_partial), merkle_proof(&state)]).to_bytes();
+    let response = account_state(mc_id.clone(), shard_id.clone(), shard_proof.clone(), proof.clone(), &account);
+    let verified = check_account_state(&mc_id, &address, &response)?;
+    assert_eq!(verified.shard_block, shard_id);
+    assert_eq!(verified.account, Some(account.clone()));
+
+    let mut stale = shard_id.clone();
+    stale.seqno -= 1;
+    let response = account_state(mc_id.clone(), stale, shard_proof, proof, &account);
+    assert!(matches!(check_account_state(&mc_id, &address, &response), Err(ProofError::ShardBlockMismatch)));
+    Ok(())
+}
+
+fn validator_set(keys: &[ed25519::KeyPair], main: u16) -> std::result::Result<Cell, BocError> {
+    let entries = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let descr = CellBuilder::new()
+                .store_uint(0x53, 8)?
+                .store_uint(0x8e81278a, 32)?
+                .store_bytes(key.public_key.as_bytes())?
+                .store_uint(10, 64)?
+                .build()?;
+            Ok((key_bits(&(i as u16).to_be_bytes(), 16), descr))
+        })
+        .collect::<std::result::Result<Vec<_>, BocError>>()?;
+    CellBuilder::new()
+        .store_uint(0x12, 8)?
+        .store_uint(0, 64)?
+        .store_uint(keys.len() as u64, 16)?
+        .store_uint(main as u64, 16)?
+        .store_uint(10 * keys.len() as u64, 64)?
+        .store_bit(true)?
+        .store_reference(make_dict(&entries, 16)?)?
+        .build()
+}
+
+/// Key block keeping the config with the validator set in its extra
+fn key_block(seqno: u32, validators: Cell) -> std::result::Result<Cell, BocError> {
+    let param = CellBuilder::new().store_reference(validators)?.build()?;
+    let config = make_dict(&[(key_bits(&34u32.to_be_bytes(), 32), param)], 32)?;
+    let mc_extra = CellBuilder::new()
+        .store_uint(MC_BLOCK_EXTRA_TAG, 16)?
+        .store_bit(true)?
+        .store_uint(0, 2)?
+        .store_coins(0)?
+        .store_bit(false)?
+        .store_coins(0)?
+        .store_bit(false)?
+        .store_reference(empty())?
+        .store_bytes(&[0; 32])?
+        .store_reference(config)?
+        .build()?;
+    let mut extra = CellBuilder::new();
+    extra.store_uint(BLOCK_EXTRA_TAG, 32)?;
+    for _ in 0..3 {
+        extra.store_reference(empty())?;
+    }
+    extra.store_bytes(&[0; 64])?.store_bit(true)?.store_reference(mc_extra)?;
+    block_cell(block_info(seqno, true)?, merkle_update(&empty(), &empty())?, extra.build()?)
+}
+
+/// Masterchain state listing `prev` among previous blocks
+fn mc_state(prev: &BlockIdExt, prev_key_block: bool) -> std::result::Result<Cell, BocError> {
+    let value = CellBuilder::new()
+        .store_bit(prev_key_block)?
+        .store_uint(0, 64)?
+        .store_bit(prev_key_block)?
+        .store_uint(0, 64)?
+        .store_uint(prev.seqno as u64, 32)?
+        .store_bytes(&prev.root_hash.0)?
+        .store_bytes(&prev.file_hash.0)?
+        .build()?;
+    let prev_blocks = make_dict(&[(key_bits(&prev.seqno.to_be_bytes(), 32), value)], 32)?;
+    let info = CellBuilder::new()
+        .store_uint(0, 16)?
+        .store_uint(0, 64)?
+        .store_bit(false)?
+        .store_bit(true)?
+        .store_reference(prev_blocks)?
+        .build()?;
+    let extra = CellBuilder::new()
+        .store_uint(MC_STATE_EXTRA_TAG, 16)?
+        .store_bit(false)?
+        .store_bytes(&[0; 32])?
+        .store_reference(empty())?
+        .store_reference(info)?
+        .build()?;
+    shard_state(-1, CellBuilder::new().store_bit(false)?.store_uint(0, 10)?.build()?, Some(extra))
+}
+
+fn mc_block_id(seqno: u32, block: &Cell) -> BlockIdExt {
+    BlockIdExt { seqno, ..block_id(-1, block) }
+}
+
+fn sign(keys: &[ed25519::KeyPair], block: &BlockIdExt) -> SignatureSet {
+    let mut payload = 0xc50b6e70u32.to_le_bytes().to_vec();
+    payload.extend_from_slice(&block.root_hash.0);
+    payload.extend_from_slice(&block.file_hash.0);
+    let signatures = keys
+        .iter()
+        .map(|key| {
+            let mut node_id = Sha256::new();
+            node_id.update(0x4813b4c6u32.to_le_bytes());
+            node_id.update(key.public_key.as_bytes());
+            Signature { node_id_short: Int256(node_id.finalize().into()), signature: key.sign_raw(&payload).to_vec() }
+        })
+        .collect();
+    SignatureSet { validator_set_hash: 0, catchain_seqno: 0, signatures }
+}
+
+#[test]
+fn test_block_proof_chain() -> TestResult {
+    let keys: Vec<_> = (0..4).map(|_| ed25519::KeyPair::generate(&mut rand::thread_rng())).collect();
+    let key_block = key_block(10, validator_set(&keys, 3)?)?;
+    let key_id = mc_block_id(10, &key_block);
+    let state = mc_state(&key_id, true)?;
+    let block = block_cell(block_info(11, false)?, merkle_update(&empty(), &state)?, empty())?;
+    let id = mc_block_id(11, &block);
+
+    let forward = |signatures: SignatureSet, to_key_block: bool| BlockLink::BlockLinkForward {
+        to_key_block,
+        from: key_id.clone(),
+        to: id.clone(),
+        dest_proof: merkle_proof(&block).to_boc(),
+        config_proof: merkle_proof(&key_block).to_boc(),
+        signatures,
+    };
+    let chain = |steps: Vec<BlockLink>, to: &BlockIdExt| PartialBlockProof { complete: true, from: key_id.clone(), to: to.clone(), steps };
+
+    let proof = chain(vec![forward(sign(&keys[..3], &id), false)], &id);
+    assert_eq!(check_partial_block_proof(&key_id, &proof)?, id);
+    assert!(matches!(check_partial_block_proof(&id, &proof), Err(ProofError::InvalidChain(_))));
+
+    let proof = chain(vec![forward(sign(&keys[..2], &id), false)], &id);
+    assert!(matches!(check_partial_block_proof(&key_id, &proof), Err(ProofError::NotEnoughSignatures { signed: 20, total: 30 })));
+    // the last validator is not in the main set of masterchain validators
+    let proof = chain(vec![forward(sign(&keys[1..], &id), false)], &id);
+    assert!(matches!(check_partial_block_proof(&key_id, &proof), Err(ProofError::UnknownValidator(_))));
+    let mut signatures = sign(&keys[..3], &id);
+    signatures.signatures[0].signature[0] ^= 1;
+    let proof = chain(vec![forward(signatures, false)], &id);
+    assert!(matches!(check_partial_block_proof(&key_id, &proof), Err(ProofError::InvalidSignature(_))));
+    let proof = chain(vec![forward(sign(&keys[..3], &id), true)], &id);
+    assert!(matches!(check_partial_block_proof(&key_id, &proof), Err(ProofError::KeyBlockMismatch)));
+
+    let back = BlockLink::BlockLinkBack {
+        to_key_block: true,
+        from: id.clone(),
+        to: key_id.clone(),
+        dest_proof: merkle_proof(&key_block).to_boc(),
+        proof: merkle_proof(&block).to_boc(),
+        state_proof: merkle_proof(&state).to_boc(),
+    };
+    let proof = chain(vec![forward(sign(&keys[..3], &id), false), back], &key_id);
+    assert_eq!(check_partial_block_proof(&key_id, &proof)?, key_id);
+    Ok(())
+}
+
+#[test]
+fn test_validator_set() -> TestResult {
+    let keys: Vec<_> = (0..4).map(|_| ed25519::KeyPair::generate(&mut rand::thread_rng())).collect();
+    let key_block = key_block(10, validator_set(&keys, 3)?)?;
+    let key_id = mc_block_id(10, &key_block);
+    let response = ConfigInfo {
+        mode: (),
+        id: key_id.clone(),
+        state_proof: Vec::new(),
+        config_proof: merkle_proof(&key_block).to_boc(),
+        with_state_root: None,
+        with_libraries: None,
+        with_state_extra_root: None,
+        with_shard_hashes: None,
+        with_validator_set: Some(()),
+        with_special_smc: None,
+        with_accounts_root: None,
+        with_prev_blocks: None,
+        with_workchain_info: None,
+        with_capabilities: None,
+        extract_from_key_block: Some(()),
+    };
+    let config = BlockchainConfig::new(check_config_info(&key_id, &response)?);
+    assert!(config.validator_set(36)?.is_none());
+    let validators = config.validator_set(34)?.ok_or("missing validator set")?;
+    assert_eq!((validators.total, validators.main, validators.total_weight), (4, 3, 40));
+    assert_eq!(validators.masterchain().len(), 3);
+    assert_eq!(validators.list[0].public_key, *keys[0].public_key.as_bytes());
+
+    let block = block_cell(block_info(11, false)?, merkle_update(&empty(), &empty())?, empty())?;
+    let id = mc_block_id(11, &block);
+    let signatures = sign(&keys[..3], &id);
+    let check = check_signatures(validators.masterchain(), &id, &signatures)?;
+    assert!(check.is_enough());
+    assert_eq!((check.signed_weight, check.total_weight), (30, 30));
+    assert_eq!(check.signers[0], validators.list[0].node_id_short());
+    // the fourth validator signs shard blocks only, all validators of the set can be checked too
+    let check = check_signatures(&validators.list, &id, &sign(&keys[2..], &id))?;
+    assert_eq!((check.signed_weight, check.total_weight), (20, 40));
+    assert!(!check.is_enough());
+
+    let mut signatures = sign(&keys[..2], &id);
+    signatures.signatures.push(signatures.signatures[0].clone());
+    assert!(matches!(check_signatures(validators.masterchain(), &id, &signatures), Err(ProofError::DuplicateSignature(_))));
+    let mut other = key_id.clone();
+    other.seqno += 1;
+    assert!(matches!(check_config_info(&other, &response), Err(ProofError::UnexpectedBlock)));
+    Ok(())
+}
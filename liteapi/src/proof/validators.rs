@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use everscale_crypto::ed25519;
+
+use crate::block::Validator;
+use crate::tl::common::{BlockIdExt, Int256, SignatureSet};
+
+use super::{ProofError, Result};
+
+/// TL id of `ton.blockId root_cell_hash:int256 file_hash:int256 = ton.BlockId`
+const TON_BLOCK_ID: u32 = 0xc50b6e70;
+
+/// Result of checking signatures of a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureCheck {
+    /// Total weight of validators with valid signatures
+    pub signed_weight: u64,
+    /// Total weight of validators the block was checked against
+    pub total_weight: u64,
+    /// Short ids of validators with valid signatures
+    pub signers: Vec<Int256>,
+}
+
+impl SignatureCheck {
+    /// Whether signed weight is more than 2/3 of the total weight, so the block is final
+    pub fn is_enough(&self) -> bool {
+        self.signed_weight as u128 * 3 > self.total_weight as u128 * 2
+    }
+}
+
+/// Data signed by validators for the block, serialized `ton.blockId`
+pub fn block_signature_payload(block: &BlockIdExt) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(4 + 32 + 32);
+    payload.extend_from_slice(&TON_BLOCK_ID.to_le_bytes());
+    payload.extend_from_slice(&block.root_hash.0);
+    payload.extend_from_slice(&block.file_hash.0);
+    payload
+}
+
+/// Checks Ed25519 signatures of the block made by `validators`.
+///
+/// For masterchain blocks `validators` are [`crate::block::ValidatorSet::masterchain`]. Signatures of unknown
+/// validators, duplicate and invalid signatures are errors.
+pub fn check_signatures(validators: &[Validator], block: &BlockIdExt, signatures: &SignatureSet) -> Result<SignatureCheck> {
+    let nodes: HashMap<Int256, usize> = validators.iter().enumerate().map(|(i, validator)| (validator.node_id_short(), i)).collect();
+    let total_weight = validators.iter().map(|validator| validator.weight).sum();
+    let payload = block_signature_payload(block);
+
+    let mut seen = vec![false; validators.len()];
+    let mut check = SignatureCheck { signed_weight: 0, total_weight, signers: Vec::with_capacity(signatures.signatures.len()) };
+    for signature in &signatures.signatures {
+        let node = &signature.node_id_short;
+        let &i = nodes.get(node).ok_or_else(|| ProofError::UnknownValidator(node.clone()))?;
+        if seen[i] {
+            return Err(ProofError::DuplicateSignature(node.clone()));
+        }
+        seen[i] = true;
+        let public_key = ed25519::PublicKey::from_bytes(validators[i].public_key);
+        let valid = match (public_key, <&[u8; 64]>::try_from(signature.signature.as_slice())) {
+            (Some(public_key), Ok(bytes)) => public_key.verify_raw(&payload, bytes),
+            _ => false,
+        };
+        if !valid {
+            return Err(ProofError::InvalidSignature(node.clone()));
+        }
+        check.signed_weight += validators[i].weight;
+        check.signers.push(node.clone());
+    }
+    Ok(check)
+}
@@ -0,0 +1,185 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use adnl::AdnlError;
+use futures::future::{BoxFuture, FutureExt as _, Shared};
+use tokio::net::{lookup_host, ToSocketAddrs};
+use tower::{Service, ServiceExt as _};
+
+use crate::client::{connect_buffered, BufferedService};
+use crate::keepalive::Keepalive;
+use crate::tl::request::WrappedRequest;
+use crate::tl::response::Response;
+use crate::types::LiteError;
+
+type Result<T> = std::result::Result<T, LiteError>;
+
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: usize = 5;
+
+type Connector = Arc<dyn Fn() -> BoxFuture<'static, Result<BufferedService>> + Send + Sync>;
+type SharedReconnect = Shared<BoxFuture<'static, std::result::Result<(u64, BufferedService), Arc<LiteError>>>>;
+
+struct State {
+    generation: u64,
+    connection: Option<BufferedService>,
+    /// Reconnection in progress, awaited by every query which needs the connection
+    reconnecting: Option<SharedReconnect>,
+}
+
+/// Lite service which rebuilds the connection to liteserver after it drops.
+///
+/// Reconnection is performed lazily by the next query, with exponential backoff between
+/// attempts. Concurrent queries share the same reconnection, and each of them may stop waiting
+/// for it without interrupting the others. Idempotent requests failed because of the broken
+/// connection are sent once again over the new one.
+#[derive(Clone)]
+pub struct Reconnect {
+    address: SocketAddr,
+    connector: Connector,
+    state: Arc<Mutex<State>>,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    max_attempts: usize,
+}
+
+impl Reconnect {
+    pub async fn connect<A: ToSocketAddrs>(address: A, public_key: impl AsRef<[u8]>) -> Result<Self> {
+        Self::connect_inner(address, public_key, None).await
+    }
+
+    /// Connects with a background heartbeat restarted on every reconnection, see [`Keepalive`]
+    pub async fn connect_with_keepalive<A: ToSocketAddrs>(address: A, public_key: impl AsRef<[u8]>, keepalive: &Keepalive) -> Result<Self> {
+        Self::connect_inner(address, public_key, Some(keepalive.clone())).await
+    }
+
+    async fn connect_inner<A: ToSocketAddrs>(address: A, public_key: impl AsRef<[u8]>, keepalive: Option<Keepalive>) -> Result<Self> {
+        let address = lookup_host(address)
+            .await
+            .map_err(AdnlError::IoError)?
+            .next()
+            .ok_or_else(|| AdnlError::IoError(io::Error::new(io::ErrorKind::NotFound, "can't resolve liteserver address")))?;
+        let connection = connect_buffered(address, public_key.as_ref(), keepalive.as_ref()).await?;
+        let public_key = Arc::new(public_key.as_ref().to_vec());
+        let connector: Connector = Arc::new(move || {
+            let public_key = public_key.clone();
+            let keepalive = keepalive.clone();
+            Box::pin(async move { connect_buffered(address, public_key.as_slice(), keepalive.as_ref()).await })
+        });
+        Ok(Self::with_connector(address, connection, connector))
+    }
+
+    pub(crate) fn with_connector(address: SocketAddr, connection: BufferedService, connector: Connector) -> Self {
+        Self {
+            address,
+            connector,
+            state: Arc::new(Mutex::new(State { generation: 0, connection: Some(connection), reconnecting: None })),
+            min_backoff: MIN_BACKOFF,
+            max_backoff: MAX_BACKOFF,
+            max_attempts: MAX_ATTEMPTS,
+        }
+    }
+
+    /// Sets the delay before the second reconnection attempt, doubled after each failure up to `max`
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets how many times to try reconnecting before failing the query
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    async fn connection(&self) -> Result<(u64, BufferedService)> {
+        let reconnect = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(connection) = &state.connection {
+                return Ok((state.generation, connection.clone()));
+            }
+            state.reconnecting.get_or_insert_with(|| self.reconnect().boxed().shared()).clone()
+        };
+        reconnect.await.map_err(|e| match &*e {
+            LiteError::Timeout => LiteError::Timeout,
+            e => LiteError::AdnlError(AdnlError::IoError(io::Error::new(io::ErrorKind::NotConnected, format!("{:?}", e)))),
+        })
+    }
+
+    /// Reconnection loop, it does not hold the state so that an abandoned one is not leaked
+    fn reconnect(&self) -> impl std::future::Future<Output = std::result::Result<(u64, BufferedService), Arc<LiteError>>> + Send + 'static {
+        let address = self.address;
+        let connector = self.connector.clone();
+        let state: Weak<Mutex<State>> = Arc::downgrade(&self.state);
+        let (min_backoff, max_backoff, max_attempts) = (self.min_backoff, self.max_backoff, self.max_attempts);
+        async move {
+            let mut delay = min_backoff;
+            let mut attempt = 1;
+            let result = loop {
+                match connector().await {
+                    Ok(connection) => {
+                        log::info!("Reconnected to liteserver {}", address);
+                        break Ok(connection);
+                    }
+                    Err(e) if attempt < max_attempts => {
+                        log::warn!("Reconnection to liteserver {} failed (attempt {}), next one in {:?}: {:?}", address, attempt, delay, e);
+                        tokio::time::sleep(delay).await;
+                        delay = (delay * 2).min(max_backoff);
+                        attempt += 1;
+                    }
+                    Err(e) => break Err(Arc::new(e)),
+                }
+            };
+            let state = state.upgrade().ok_or_else(|| Arc::new(LiteError::AdnlError(AdnlError::EndOfStream)))?;
+            let mut state = state.lock().unwrap();
+            state.reconnecting = None;
+            let connection = result?;
+            state.generation += 1;
+            state.connection = Some(connection.clone());
+            Ok((state.generation, connection))
+        }
+    }
+
+    async fn send(&self, request: WrappedRequest) -> Result<Response> {
+        let (generation, mut connection) = self.connection().await?;
+        let result = match connection.ready().await {
+            Ok(connection) => connection.call(request).await,
+            Err(e) => Err(e),
+        }.map_err(LiteError::from_boxed);
+        if let Err(e) = &result {
+            if e.is_transport() {
+                log::warn!("Connection to liteserver {} lost: {:?}", self.address, e);
+                let mut state = self.state.lock().unwrap();
+                if state.generation == generation {
+                    state.connection = None;
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Service<WrappedRequest> for Reconnect {
+    type Response = Response;
+    type Error = LiteError;
+    type Future = BoxFuture<'static, Result<Response>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: WrappedRequest) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move {
+            match this.send(request.clone()).await {
+                Err(e) if e.is_transport() && request.request.is_idempotent() => this.send(request).await,
+                result => result,
+            }
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use crate::client::LiteClient;
+use crate::proof::VerifiedAccountState;
+use crate::tl::common::{AccountId, BlockIdExt};
+use crate::tl::request::{GetConfigAll, GetConfigParams};
+use crate::tl::response::{AccountState, AllShardsInfo, ConfigInfo, ShardInfo};
+use crate::tvm::{GetMethodResult, TvmStackEntry};
+use crate::types::LiteError;
+
+type Result<T> = std::result::Result<T, LiteError>;
+
+/// View of the blockchain pinned to a single masterchain block, see [`LiteClient::at`].
+///
+/// Every query made through the snapshot is answered at the same masterchain block, so results
+/// of many queries are consistent with each other. Shard blocks holding the accounts are
+/// resolved by liteserver from the masterchain block.
+#[derive(Clone)]
+pub struct BlockSnapshot {
+    client: LiteClient,
+    mc_block: BlockIdExt,
+}
+
+impl BlockSnapshot {
+    pub(crate) fn new(client: LiteClient, mc_block: BlockIdExt) -> Self {
+        // liteservers lagging behind have to catch up first instead of failing with unknown block
+        let client = client.default_wait_masterchain_seqno(mc_block.seqno);
+        Self { client, mc_block }
+    }
+
+    pub fn mc_block(&self) -> &BlockIdExt {
+        &self.mc_block
+    }
+
+    pub async fn get_account_state(&self, account: AccountId) -> Result<AccountState> {
+        self.client.get_account_state(self.mc_block.clone(), account).await
+    }
+
+    pub async fn get_account_state_verified(&self, account: AccountId) -> Result<VerifiedAccountState> {
+        self.client.get_account_state_verified(self.mc_block.clone(), account).await
+    }
+
+    pub async fn run_get_method(&self, account: AccountId, method_id: u64, params: &[TvmStackEntry]) -> Result<GetMethodResult> {
+        self.client.run_get_method(self.mc_block.clone(), account, method_id, params).await
+    }
+
+    pub async fn run_get_method_verified(&self, account: AccountId, method_id: u64, params: &[TvmStackEntry]) -> Result<(VerifiedAccountState, GetMethodResult)> {
+        self.client.run_get_method_verified(self.mc_block.clone(), account, method_id, params).await
+    }
+
+    pub async fn get_shard_info(&self, workchain: i32, shard: u64, exact: bool) -> Result<ShardInfo> {
+        self.client.get_shard_info(self.mc_block.clone(), workchain, shard, exact).await
+    }
+
+    pub async fn get_all_shards_info(&self) -> Result<AllShardsInfo> {
+        self.client.get_all_shards_info(self.mc_block.clone()).await
+    }
+
+    /// Shard block containing given account
+    pub async fn get_shard_block(&self, account: &AccountId) -> Result<BlockIdExt> {
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&account.id.0[..8]);
+        // the deepest possible shard, liteserver returns the actual one containing it
+        let shard = u64::from_be_bytes(prefix) | 1;
+        let response = self.get_shard_info(account.workchain, shard, false).await?;
+        Ok(response.shardblk)
+    }
+
+    pub async fn get_config_all(&self) -> Result<ConfigInfo> {
+        self.client.get_config_all(GetConfigAll::new(self.mc_block.clone())).await
+    }
+
+    pub async fn get_config_params(&self, param_list: Vec<i32>) -> Result<ConfigInfo> {
+        self.client.get_config_params(GetConfigParams::new(self.mc_block.clone(), param_list)).await
+    }
+}
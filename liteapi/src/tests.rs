@@ -0,0 +1,328 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use adnl::AdnlError;
+use futures::future::{self, join_all};
+use tower::buffer::Buffer;
+use tower::util::BoxService;
+use tower::{service_fn, Service, ServiceBuilder, ServiceExt as _};
+
+use crate::client::{BufferedService, LiteClient};
+use crate::keepalive::{Keepalive, KeepaliveService};
+use crate::layers::{RetryLayer, RetryPolicy, UnwrapErrorLayer, WrapMessagesLayer};
+use crate::pool::LitePool;
+use crate::reconnect::Reconnect;
+use crate::tl::adnl::Message;
+use crate::tl::request::{LiteQuery, Request, SendMessage, WrappedRequest};
+use crate::tl::response::{CurrentTime, Error, Response};
+use crate::types::{LiteError, ERROR_CODE_NOT_READY, ERROR_CODE_TIMEOUT};
+
+/// Liteserver stub answering `getTime` unless it is switched to failing with a broken connection
+#[derive(Clone, Default)]
+struct MockServer {
+    failing: Arc<AtomicBool>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    fn set_failing(&self, failing: bool) {
+        self.failing.store(failing, Ordering::Relaxed);
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    fn service(&self) -> BufferedService {
+        let server = self.clone();
+        Buffer::new(BoxService::new(service_fn(move |_: WrappedRequest| {
+            let server = server.clone();
+            async move {
+                server.calls.fetch_add(1, Ordering::Relaxed);
+                if server.failing.load(Ordering::Relaxed) {
+                    Err(LiteError::AdnlError(AdnlError::EndOfStream))
+                } else {
+                    Ok(Response::CurrentTime(CurrentTime { now: 1 }))
+                }
+            }
+        })), 10)
+    }
+}
+
+fn get_time() -> WrappedRequest {
+    WrappedRequest { wait_masterchain_seqno: None, request: Request::GetTime }
+}
+
+async fn query<S: Service<WrappedRequest, Response = Response, Error = LiteError>>(service: &mut S) -> Result<Response, LiteError> {
+    service.ready().await?.call(get_time()).await
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pool_failover() {
+    let servers = [MockServer::default(), MockServer::default()];
+    let mut pool = LitePool::from_services(servers.iter().map(MockServer::service).collect());
+    servers[0].set_failing(true);
+    for _ in 0..4 {
+        assert!(query(&mut pool).await.is_ok());
+    }
+    // the failed liteserver is skipped while cooling down
+    assert_eq!(pool.alive_count(), 1);
+    assert_eq!((servers[0].calls(), servers[1].calls()), (1, 4));
+
+    servers[1].set_failing(true);
+    assert!(matches!(query(&mut pool).await, Err(LiteError::AdnlError(_))));
+    assert_eq!(pool.alive_count(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pool_recovery() {
+    let servers = [MockServer::default(), MockServer::default()];
+    let mut pool = LitePool::from_services(servers.iter().map(MockServer::service).collect()).cooldown(Duration::from_secs(5));
+    servers[0].set_failing(true);
+    query(&mut pool).await.unwrap();
+    servers[0].set_failing(false);
+    tokio::time::advance(Duration::from_secs(4)).await;
+    assert_eq!(pool.alive_count(), 1);
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert_eq!(pool.alive_count(), 2);
+    for _ in 0..4 {
+        query(&mut pool).await.unwrap();
+    }
+    assert_eq!((servers[0].calls(), servers[1].calls()), (3, 3));
+
+    // when every liteserver is cooling down, they are still tried
+    servers.iter().for_each(|s| s.set_failing(true));
+    assert!(query(&mut pool).await.is_err());
+    assert_eq!(pool.alive_count(), 0);
+    servers[1].set_failing(false);
+    query(&mut pool).await.unwrap();
+    assert_eq!(pool.alive_count(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reconnect_shared() {
+    let broken = MockServer::default();
+    broken.set_failing(true);
+    let server = MockServer::default();
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let (server, attempts) = (server.clone(), attempts.clone());
+        Arc::new(move || {
+            let attempt = attempts.fetch_add(1, Ordering::Relaxed) + 1;
+            let service = server.service();
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                if attempt < 3 {
+                    Err(LiteError::AdnlError(AdnlError::EndOfStream))
+                } else {
+                    Ok(service)
+                }
+            }) as _
+        })
+    };
+    let reconnect = Reconnect::with_connector("127.0.0.1:0".parse().unwrap(), broken.service(), connector)
+        .backoff(Duration::from_secs(1), Duration::from_secs(1));
+
+    // a query giving up early does not stop the reconnection for the others
+    let mut impatient = reconnect.clone();
+    let impatient = tokio::time::timeout(Duration::from_millis(500), async move { query(&mut impatient).await });
+    assert!(impatient.await.is_err());
+    let queries = (0..5).map(|_| {
+        let mut reconnect = reconnect.clone();
+        async move { query(&mut reconnect).await }
+    });
+    for result in join_all(queries).await {
+        result.unwrap();
+    }
+    assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    assert_eq!((broken.calls(), server.calls()), (1, 5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reconnect_failure() {
+    let broken = MockServer::default();
+    broken.set_failing(true);
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let attempts = attempts.clone();
+        Arc::new(move || {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Box::pin(async { Err(LiteError::Timeout) }) as _
+        })
+    };
+    let mut reconnect = Reconnect::with_connector("127.0.0.1:0".parse().unwrap(), broken.service(), connector).max_attempts(2);
+    assert!(matches!(query(&mut reconnect).await, Err(LiteError::Timeout)));
+    assert_eq!(attempts.load(Ordering::Relaxed), 2);
+    // the next query starts a new reconnection, and one more when it is repeated
+    assert!(query(&mut reconnect).await.is_err());
+    assert_eq!(attempts.load(Ordering::Relaxed), 6);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_keepalive() {
+    let keepalive = Keepalive::new(Duration::from_secs(1), 2);
+    let _guard = keepalive.start(service_fn(|ping: Message| async move {
+        match ping {
+            Message::Ping { random_id } => Ok(Message::Pong { random_id }),
+            _ => Err(LiteError::UnexpectedMessage),
+        }
+    }));
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert!(keepalive.is_alive());
+    assert_eq!(keepalive.missed_pongs(), 0);
+    assert!(keepalive.latency().is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_keepalive_dead_connection() {
+    let keepalive = Keepalive::new(Duration::from_secs(1), 2);
+    // transport which never answers pings
+    let guard = keepalive.start(service_fn(|_: Message| future::pending::<Result<Message, LiteError>>()));
+    let server = MockServer::default();
+    let mut service = KeepaliveService::new(server.service().map_err(LiteError::from_boxed), keepalive.clone(), guard);
+    query(&mut service).await.unwrap();
+
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert!(!keepalive.is_alive());
+    assert_eq!(keepalive.missed_pongs(), 2);
+    let error = query(&mut service).await.unwrap_err();
+    assert!(matches!(error, LiteError::AdnlError(AdnlError::EndOfStream)));
+    assert!(error.is_transport());
+    assert_eq!(server.calls(), 1);
+}
+
+/// Client recording `waitMasterchainSeqno` of every request, answering after `delay`
+fn recording_client(delay: Duration) -> (LiteClient, Arc<Mutex<Vec<Option<u32>>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let client = LiteClient::new(service_fn({
+        let seen = seen.clone();
+        move |request: WrappedRequest| {
+            seen.lock().unwrap().push(request.wait_masterchain_seqno.map(|wait| wait.seqno));
+            async move {
+                tokio::time::sleep(delay).await;
+                Ok(Response::CurrentTime(CurrentTime { now: 1 }))
+            }
+        }
+    }));
+    (client, seen)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_client_wait_masterchain_seqno() {
+    let (client, seen) = recording_client(Duration::ZERO);
+    let client = client.wait_masterchain_seqno(10);
+    client.get_time().await.unwrap();
+    client.get_time().await.unwrap();
+    let client = client.default_wait_masterchain_seqno(20);
+    client.get_time().await.unwrap();
+    let client = client.wait_masterchain_seqno(30);
+    client.get_time().await.unwrap();
+    client.get_time().await.unwrap();
+    assert_eq!(*seen.lock().unwrap(), [Some(10), None, Some(20), Some(30), Some(20)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_client_timeout() {
+    let (client, _) = recording_client(Duration::from_secs(2));
+    let client = client.default_timeout(Duration::from_secs(3)).timeout(Duration::from_secs(1));
+    assert!(matches!(client.get_time().await, Err(LiteError::Timeout)));
+    assert_eq!(client.get_time().await.unwrap(), 1);
+    let client = client.default_timeout(Duration::from_secs(1));
+    assert!(matches!(client.get_time().await, Err(LiteError::Timeout)));
+    assert_eq!(client.timeout(Duration::from_secs(3)).get_time().await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_client_query_raw() {
+    let queries = Arc::new(Mutex::new(Vec::new()));
+    // liteserver knowing a method which the crate does not, the messages go through serialization as on the wire
+    let transport = service_fn({
+        let queries = queries.clone();
+        move |message: Message| {
+            let (query_id, query) = match tl_proto::deserialize(&tl_proto::serialize(message)) {
+                Ok(Message::Query { query_id, query }) => (query_id, query),
+                _ => unreachable!(),
+            };
+            let answer = match &query.wrapped_request.request {
+                Request::GetTime => tl_proto::serialize(Response::CurrentTime(CurrentTime { now: 7 })),
+                Request::Raw(_) => hex::decode("efbeadde01020304").unwrap(),
+                _ => tl_proto::serialize(Response::Error(Error { code: 1, message: "unknown".into() })),
+            };
+            queries.lock().unwrap().push(tl_proto::serialize(query));
+            let answer = Message::Answer { query_id, answer: Response::Raw(answer) };
+            future::ready(tl_proto::deserialize::<Message>(&tl_proto::serialize(answer)).map_err(LiteError::TlError))
+        }
+    });
+    let service = ServiceBuilder::new().layer(UnwrapErrorLayer).layer(WrapMessagesLayer).service(transport);
+    let client = LiteClient::new(service).default_wait_masterchain_seqno(5);
+
+    let request = hex::decode("0a0b0c0d11223344").unwrap();
+    assert_eq!(client.query_raw(&request).await.unwrap(), hex::decode("efbeadde01020304").unwrap());
+    assert_eq!(client.get_time().await.unwrap(), 7);
+    // raw requests are sent without waitMasterchainSeqno, as given
+    let query = LiteQuery { wrapped_request: WrappedRequest { wait_masterchain_seqno: None, request: Request::Raw(request) } };
+    assert_eq!(queries.lock().unwrap()[0], hex::decode("df068c79080a0b0c0d11223344000000").unwrap());
+    assert_eq!(queries.lock().unwrap()[0], tl_proto::serialize(query));
+}
+
+#[test]
+fn test_transient_errors() {
+    let server_error = |code, message: &str| LiteError::ServerError(Error { code, message: message.into() });
+    assert!(server_error(ERROR_CODE_NOT_READY, "block is not applied").is_transient());
+    assert!(server_error(ERROR_CODE_TIMEOUT, "timeout").is_transient());
+    assert!(server_error(0, "state already gc'd or not in db").is_transient());
+    assert!(!server_error(0, "cannot load block").is_transient());
+    assert!(!server_error(0, "").is_transient());
+    assert!(LiteError::Timeout.is_transient());
+    assert!(LiteError::AdnlError(AdnlError::EndOfStream).is_transient());
+    assert!(!LiteError::UnexpectedMessage.is_transient());
+}
+
+/// Service failing every query with the given error and recording when it was called
+fn failing_service(
+    error: fn() -> LiteError,
+    calls: Arc<Mutex<Vec<tokio::time::Instant>>>,
+) -> impl Service<WrappedRequest, Response = Response, Error = LiteError, Future = future::Ready<Result<Response, LiteError>>> + Clone {
+    service_fn(move |_: WrappedRequest| {
+        calls.lock().unwrap().push(tokio::time::Instant::now());
+        future::ready(Err(error()))
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_backoff() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let policy = RetryPolicy::new(4).backoff(Duration::from_millis(100), Duration::from_millis(250));
+    let mut service = ServiceBuilder::new().layer(RetryLayer::new(policy)).service(failing_service(|| LiteError::Timeout, calls.clone()));
+    assert!(matches!(query(&mut service).await, Err(LiteError::Timeout)));
+    let calls = calls.lock().unwrap();
+    let delays: Vec<_> = calls.windows(2).map(|w| (w[1] - w[0]).as_millis()).collect();
+    // doubled after each failure, up to the maximum
+    assert_eq!(delays, [100, 200, 250, 250]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_non_idempotent() {
+    let send_message = || WrappedRequest { wait_masterchain_seqno: None, request: Request::SendMessage(SendMessage { body: vec![1, 2, 3] }) };
+    let retry = || RetryLayer::new(RetryPolicy::new(3));
+
+    // a message lost with the connection may have been delivered, so it is not sent again
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut service = ServiceBuilder::new().layer(retry()).service(failing_service(|| LiteError::Timeout, calls.clone()));
+    assert!(service.ready().await.unwrap().call(send_message()).await.is_err());
+    assert_eq!(calls.lock().unwrap().len(), 1);
+
+    // unless liteserver explicitly refused it
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let refused = || LiteError::ServerError(Error { code: ERROR_CODE_NOT_READY, message: "not ready".into() });
+    let mut service = ServiceBuilder::new().layer(retry()).service(failing_service(refused, calls.clone()));
+    assert!(service.ready().await.unwrap().call(send_message()).await.is_err());
+    assert_eq!(calls.lock().unwrap().len(), 4);
+
+    // permanent errors are not retried either
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut service = ServiceBuilder::new().layer(retry()).service(failing_service(|| LiteError::UnexpectedMessage, calls.clone()));
+    assert!(query(&mut service).await.is_err());
+    assert_eq!(calls.lock().unwrap().len(), 1);
+}
@@ -16,7 +16,7 @@ pub enum Message {
 
     /// adnl.message.answer query_id:int256 answer:bytes = adnl.Message;
     #[tl(id = 0x0fac8416)]
-    Answer { query_id: Int256, #[tl(with = "struct_as_bytes")] answer: Response },
+    Answer { query_id: Int256, #[tl(with = "answer_as_bytes")] answer: Response },
 
     /// tcp.ping random_id:long = tcp.Pong;
     #[tl(id = 0x4d082b9a)]
@@ -3,6 +3,7 @@ use std::{fmt::Display, str::FromStr};
 
 use derivative::Derivative;
 use hex::FromHex;
+use thiserror::Error;
 use tl_proto::{TlRead, TlWrite};
 use super::utils::*;
 
@@ -36,6 +37,10 @@ impl String {
     pub fn new(str: std::string::String) -> Self {
         Self(str.into_bytes())
     }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
 }
 
 /// int256 8*[ int ] = Int256;
@@ -70,6 +75,28 @@ impl Int256 {
     }
 }
 
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseIdError {
+    #[error("Wrong format, must be {0}")]
+    InvalidFormat(&'static str),
+    #[error("Can't parse {0}")]
+    InvalidField(&'static str),
+}
+
+/// Parses hash in hex or in standard or URL-safe base64
+fn parse_hash(s: &str, field: &'static str) -> Result<Int256, ParseIdError> {
+    let bytes = if s.len() == 64 {
+        hex::decode(s).ok()
+    } else {
+        base64::decode(s).or_else(|_| base64::decode_config(s, base64::URL_SAFE)).ok()
+    };
+    bytes.and_then(|bytes| bytes.try_into().ok()).map(Int256).ok_or(ParseIdError::InvalidField(field))
+}
+
+fn parse_field<T: FromStr>(s: &str, field: &'static str) -> Result<T, ParseIdError> {
+    s.parse().map_err(|_| ParseIdError::InvalidField(field))
+}
+
 /// tonNode.blockId workchain:int shard:long seqno:int = tonNode.BlockId;
 #[derive(TlRead, TlWrite, Derivative)]
 #[derivative(Debug, Clone, PartialEq)]
@@ -79,6 +106,31 @@ pub struct BlockId {
     pub seqno: u32,
 }
 
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{:X},{})", self.workchain, self.shard, self.seqno)
+    }
+}
+
+/// Parses `(workchain,shard_hex,seqno)`
+impl FromStr for BlockId {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const FORMAT: &str = "(workchain,shard_hex,seqno)";
+        let inner = s.strip_prefix('(').and_then(|s| s.strip_suffix(')')).ok_or(ParseIdError::InvalidFormat(FORMAT))?;
+        let mut fields = inner.split(',');
+        let (Some(workchain), Some(shard), Some(seqno), None) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+            return Err(ParseIdError::InvalidFormat(FORMAT));
+        };
+        Ok(Self {
+            workchain: parse_field(workchain, "workchain")?,
+            shard: u64::from_str_radix(shard, 16).map_err(|_| ParseIdError::InvalidField("shard"))?,
+            seqno: parse_field(seqno, "seqno")?,
+        })
+    }
+}
+
 /// tonNode.blockIdExt workchain:int shard:long seqno:int root_hash:int256 file_hash:int256 = tonNode.BlockIdExt;
 #[derive(TlRead, TlWrite, Derivative)]
 #[derivative(Debug, Clone, PartialEq, Eq, Hash)]
@@ -96,6 +148,30 @@ impl fmt::Display for BlockIdExt {
     }
 }
 
+/// Parses `(workchain,shard_hex,seqno):root_hash:file_hash`, hashes are in hex or base64
+impl FromStr for BlockIdExt {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const FORMAT: &str = "(workchain,shard_hex,seqno):root_hash:file_hash";
+        let mut fields = s.split(':');
+        let (Some(id), Some(root_hash), Some(file_hash), None) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+            return Err(ParseIdError::InvalidFormat(FORMAT));
+        };
+        let BlockId { workchain, shard, seqno } = id.parse().map_err(|e| match e {
+            ParseIdError::InvalidFormat(_) => ParseIdError::InvalidFormat(FORMAT),
+            e => e,
+        })?;
+        Ok(Self {
+            workchain,
+            shard,
+            seqno,
+            root_hash: parse_hash(root_hash, "root_hash")?,
+            file_hash: parse_hash(file_hash, "file_hash")?,
+        })
+    }
+}
+
 /// liteServer.accountId workchain:int id:int256 = liteServer.AccountId;
 #[derive(TlRead, TlWrite, Derivative)]
 #[derivative(Debug, Clone, PartialEq)]
@@ -104,6 +180,13 @@ pub struct AccountId {
     pub id: Int256,
 }
 
+/// Raw form of address, `workchain:hex`
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.workchain, self.id)
+    }
+}
+
 /// liteServer.transactionId3 account:int256 lt:long = liteServer.TransactionId3;
 #[derive(TlRead, TlWrite, Derivative)]
 #[derivative(Debug, Clone, PartialEq)]
@@ -183,6 +266,30 @@ pub struct ZeroStateIdExt {
     pub file_hash: Int256,
 }
 
+impl fmt::Display for ZeroStateIdExt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.workchain, self.root_hash, self.file_hash)
+    }
+}
+
+/// Parses `workchain:root_hash:file_hash`, hashes are in hex or base64
+impl FromStr for ZeroStateIdExt {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const FORMAT: &str = "workchain:root_hash:file_hash";
+        let mut fields = s.split(':');
+        let (Some(workchain), Some(root_hash), Some(file_hash), None) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+            return Err(ParseIdError::InvalidFormat(FORMAT));
+        };
+        Ok(Self {
+            workchain: parse_field(workchain, "workchain")?,
+            root_hash: parse_hash(root_hash, "root_hash")?,
+            file_hash: parse_hash(file_hash, "file_hash")?,
+        })
+    }
+}
+
 /// liteServer.transactionId mode:# account:mode.0?int256 lt:mode.1?long hash:mode.2?int256 = liteServer.TransactionId;
 #[derive(TlRead, TlWrite, Derivative)]
 #[derivative(Debug, Clone, PartialEq)]
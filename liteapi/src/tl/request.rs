@@ -22,9 +22,40 @@ pub struct LiteQuery {
 pub struct WrappedRequest {
     #[tl(read_with = "lossy_read")]
     pub wait_masterchain_seqno: Option<WaitMasterchainSeqno>,
+    #[tl(with = "raw_request")]
     pub request: Request,
 }
 
+/// [`Request::Raw`] is written as is, without the constructor
+mod raw_request {
+    use tl_proto::{TlPacket, TlRead, TlResult, TlWrite};
+
+    use super::Request;
+
+    pub fn size_hint(v: &Request) -> usize {
+        match v {
+            Request::Raw(data) => data.len(),
+            v => v.max_size_hint(),
+        }
+    }
+
+    pub fn write<P: TlPacket>(v: &Request, packet: &mut P) {
+        match v {
+            Request::Raw(data) => packet.write_raw_slice(data),
+            v => v.write_to(packet),
+        }
+    }
+
+    /// Requests with unknown constructors are read as [`Request::Raw`] up to the end of the query
+    pub fn read(packet: &[u8], offset: &mut usize) -> TlResult<Request> {
+        let start = *offset;
+        Request::read_from(packet, offset).or_else(|_| {
+            *offset = packet.len();
+            Ok(Request::Raw(packet[start..].to_vec()))
+        })
+    }
+}
+
 /// liteServer.query data:bytes = Object;
 #[derive(TlRead, TlWrite, Derivative)]
 #[derivative(Debug, Clone, PartialEq)]
@@ -90,11 +121,27 @@ pub struct GetAccountState {
 #[derive(TlRead, TlWrite, Derivative)]
 #[derivative(Debug, Clone, PartialEq)]
 pub struct RunSmcMethod {
-    pub mode: u32,
+    #[tl(flags)]
+    pub mode: (),
     pub id: BlockIdExt,
     pub account: AccountId,
     pub method_id: u64,
     pub params: Vec<u8>,
+    /// `shard_proof` and `proof` of the account state
+    #[tl(flags_bit = "mode.0")]
+    pub with_proofs: Option<()>,
+    /// `state_proof`, Merkle proof of the account cells used by the get-method
+    #[tl(flags_bit = "mode.1")]
+    pub with_state_proof: Option<()>,
+    /// `result`, the result stack
+    #[tl(flags_bit = "mode.2")]
+    pub with_result: Option<()>,
+    /// `init_c7`, the c7 register of the get-method
+    #[tl(flags_bit = "mode.3")]
+    pub with_init_c7: Option<()>,
+    /// `lib_extras`, the libraries used by the get-method
+    #[tl(flags_bit = "mode.4")]
+    pub with_lib_extras: Option<()>,
 }
 
 #[derive(TlRead, TlWrite, Derivative)]
@@ -308,7 +355,7 @@ pub struct GetOutMsgQueueSizes {
     #[tl(flags)]
     pub mode: (),
     #[tl(flags_bit = "mode.0")]
-    pub shard_id: Option<(u32, u64)>,
+    pub shard_id: Option<(i32, u64)>,
 }
 
 #[derive(TlRead, TlWrite, Derivative)]
@@ -474,4 +521,289 @@ pub enum Request {
     /// liteServer.getDispatchQueueMessages mode:# id:tonNode.blockIdExt addr:int256 after_lt:long max_messages:int want_proof:mode.0?true one_account:mode.1?true messages_boc:mode.2?true = liteServer.DispatchQueueMessages;
     #[tl(id = 0xbbfd6439)]
     GetDispatchQueueMessages(GetDispatchQueueMessages),
+
+    /// Pre-serialized content of `liteServer.query`, sent as is by [`WrappedRequest`] and answered
+    /// with [`Response::Raw`](super::response::Response::Raw). On its own it is written as
+    /// `liteServer.query data:bytes = Object;`
+    #[tl(id = 0x798c06df)]
+    Raw(#[derivative(Debug(format_with = "fmt_bytes"))] Vec<u8>),
+}
+
+macro_rules! flag_setters {
+    ($($flag:ident),* $(,)?) => {
+        $(
+            pub fn $flag(mut self) -> Self {
+                self.$flag = Some(());
+                self
+            }
+        )*
+    };
+}
+
+impl GetBlockHeader {
+    pub fn new(id: BlockIdExt) -> Self {
+        Self {
+            id,
+            mode: (),
+            with_state_update: None,
+            with_value_flow: None,
+            with_extra: None,
+            with_shard_hashes: None,
+            with_prev_blk_signatures: None,
+        }
+    }
+
+    flag_setters!(with_state_update, with_value_flow, with_extra, with_shard_hashes, with_prev_blk_signatures);
+}
+
+impl LookupBlock {
+    fn new(id: BlockId) -> Self {
+        Self {
+            mode: (),
+            id,
+            seqno: None,
+            lt: None,
+            utime: None,
+            with_state_update: None,
+            with_value_flow: None,
+            with_extra: None,
+            with_shard_hashes: None,
+            with_prev_blk_signatures: None,
+        }
+    }
+
+    /// Looks up the block with exactly given seqno
+    pub fn by_seqno(id: BlockId) -> Self {
+        Self { seqno: Some(()), ..Self::new(id) }
+    }
+
+    /// Looks up the block of the shard containing given logical time
+    pub fn by_lt(workchain: i32, shard: u64, lt: u64) -> Self {
+        Self { lt: Some(lt), ..Self::new(BlockId { workchain, shard, seqno: 0 }) }
+    }
+
+    /// Looks up the block of the shard generated at given unix time
+    pub fn by_utime(workchain: i32, shard: u64, utime: u32) -> Self {
+        Self { utime: Some(utime), ..Self::new(BlockId { workchain, shard, seqno: 0 }) }
+    }
+
+    flag_setters!(with_state_update, with_value_flow, with_extra, with_shard_hashes, with_prev_blk_signatures);
+}
+
+impl LookupBlockWithProof {
+    fn new(id: BlockId, mc_block_id: BlockIdExt) -> Self {
+        Self {
+            mode: (),
+            id,
+            mc_block_id,
+            seqno: None,
+            lt: None,
+            utime: None,
+            with_state_update: None,
+            with_value_flow: None,
+            with_extra: None,
+            with_shard_hashes: None,
+            with_prev_blk_signatures: None,
+        }
+    }
+
+    /// Looks up the block with exactly given seqno, proving it against `mc_block_id`
+    pub fn by_seqno(id: BlockId, mc_block_id: BlockIdExt) -> Self {
+        Self { seqno: Some(()), ..Self::new(id, mc_block_id) }
+    }
+
+    /// Looks up the block of the shard containing given logical time, proving it against `mc_block_id`
+    pub fn by_lt(workchain: i32, shard: u64, lt: u64, mc_block_id: BlockIdExt) -> Self {
+        Self { lt: Some(lt), ..Self::new(BlockId { workchain, shard, seqno: 0 }, mc_block_id) }
+    }
+
+    /// Looks up the block of the shard generated at given unix time, proving it against `mc_block_id`
+    pub fn by_utime(workchain: i32, shard: u64, utime: u32, mc_block_id: BlockIdExt) -> Self {
+        Self { utime: Some(utime), ..Self::new(BlockId { workchain, shard, seqno: 0 }, mc_block_id) }
+    }
+
+    flag_setters!(with_state_update, with_value_flow, with_extra, with_shard_hashes, with_prev_blk_signatures);
+}
+
+impl ListBlockTransactions {
+    pub fn new(id: BlockIdExt, count: u32) -> Self {
+        Self {
+            id,
+            mode: (),
+            count,
+            after: None,
+            reverse_order: None,
+            want_proof: None,
+        }
+    }
+
+    /// Lists transactions starting after given one
+    pub fn after(mut self, after: TransactionId3) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    flag_setters!(reverse_order, want_proof);
+}
+
+impl GetBlockProof {
+    pub fn new(known_block: BlockIdExt) -> Self {
+        Self {
+            mode: (),
+            known_block,
+            target_block: None,
+            allow_weak_target: None,
+            base_block_from_request: None,
+        }
+    }
+
+    /// Proves given block instead of the latest masterchain block
+    pub fn target_block(mut self, target_block: BlockIdExt) -> Self {
+        self.target_block = Some(target_block);
+        self
+    }
+
+    flag_setters!(allow_weak_target, base_block_from_request);
+}
+
+impl GetConfigAll {
+    pub fn new(id: BlockIdExt) -> Self {
+        Self {
+            mode: (),
+            id,
+            with_state_root: None,
+            with_libraries: None,
+            with_state_extra_root: None,
+            with_shard_hashes: None,
+            with_validator_set: None,
+            with_special_smc: None,
+            with_accounts_root: None,
+            with_prev_blocks: None,
+            with_workchain_info: None,
+            with_capabilities: None,
+            extract_from_key_block: None,
+        }
+    }
+
+    flag_setters!(
+        with_state_root,
+        with_libraries,
+        with_state_extra_root,
+        with_shard_hashes,
+        with_validator_set,
+        with_special_smc,
+        with_accounts_root,
+        with_prev_blocks,
+        with_workchain_info,
+        with_capabilities,
+        extract_from_key_block,
+    );
+}
+
+impl GetConfigParams {
+    pub fn new(id: BlockIdExt, param_list: Vec<i32>) -> Self {
+        Self {
+            mode: (),
+            id,
+            param_list,
+            with_state_root: None,
+            with_libraries: None,
+            with_state_extra_root: None,
+            with_shard_hashes: None,
+            with_validator_set: None,
+            with_special_smc: None,
+            with_accounts_root: None,
+            with_prev_blocks: None,
+            with_workchain_info: None,
+            with_capabilities: None,
+            extract_from_key_block: None,
+        }
+    }
+
+    flag_setters!(
+        with_state_root,
+        with_libraries,
+        with_state_extra_root,
+        with_shard_hashes,
+        with_validator_set,
+        with_special_smc,
+        with_accounts_root,
+        with_prev_blocks,
+        with_workchain_info,
+        with_capabilities,
+        extract_from_key_block,
+    );
+}
+
+impl GetBlockOutMsgQueueSize {
+    pub fn new(id: BlockIdExt) -> Self {
+        Self { mode: (), id, want_proof: None }
+    }
+
+    flag_setters!(want_proof);
+}
+
+impl GetDispatchQueueInfo {
+    pub fn new(id: BlockIdExt, max_accounts: u32) -> Self {
+        Self {
+            mode: (),
+            id,
+            want_proof: None,
+            after_addr: None,
+            max_accounts,
+        }
+    }
+
+    /// Lists dispatch queues of accounts starting after given one
+    pub fn after_addr(mut self, after_addr: Int256) -> Self {
+        self.after_addr = Some(after_addr);
+        self
+    }
+
+    flag_setters!(want_proof);
+}
+
+impl GetDispatchQueueMessages {
+    pub fn new(id: BlockIdExt, addr: Int256, after_lt: u64, max_messages: u32) -> Self {
+        Self {
+            mode: (),
+            id,
+            addr,
+            after_lt,
+            max_messages,
+            want_proof: None,
+            one_account: None,
+            message_boc: None,
+        }
+    }
+
+    flag_setters!(want_proof, one_account, message_boc);
+}
+
+impl RunSmcMethod {
+    pub fn new(id: BlockIdExt, account: AccountId, method_id: u64, params: Vec<u8>) -> Self {
+        Self {
+            mode: (),
+            id,
+            account,
+            method_id,
+            params,
+            with_proofs: None,
+            with_state_proof: None,
+            with_result: None,
+            with_init_c7: None,
+            with_lib_extras: None,
+        }
+    }
+
+    flag_setters!(with_proofs, with_state_proof, with_result, with_init_c7, with_lib_extras);
+}
+
+impl Request {
+    /// Whether the request can be safely repeated, e.g. after a reconnect.
+    ///
+    /// Raw requests are never considered idempotent, since they may send messages too.
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, Request::SendMessage(_) | Request::Raw(_))
+    }
 }
@@ -471,6 +471,10 @@ pub enum Response {
     #[tl(id = 0xbd8cad2b)]
     BlockTransactions(BlockTransactions),
 
+    /// liteServer.blockTransactionsExt id:tonNode.blockIdExt req_count:# incomplete:Bool transactions:bytes proof:bytes = liteServer.BlockTransactionsExt;
+    #[tl(id = 0xfb8ffce4)]
+    BlockTransactionsExt(BlockTransactionsExt),
+
     /// liteServer.partialBlockProof complete:Bool from:tonNode.blockIdExt to:tonNode.blockIdExt steps:(vector liteServer.BlockLink) = liteServer.PartialBlockProof;
     #[tl(id = 0x8ed0d2c1)]
     PartialBlockProof(PartialBlockProof),
@@ -518,4 +522,9 @@ pub enum Response {
     /// liteServer.error code:int message:string = liteServer.Error;
     #[tl(id = 0xbba9e148)]
     Error(Error),
+
+    /// Undecoded answer to [`Request::Raw`](super::request::Request::Raw), written as is into
+    /// `adnl.message.answer`
+    #[tl(id = 0x798c06df)]
+    Raw(#[derivative(Debug(format_with = "fmt_bytes"))] Vec<u8>),
 }
\ No newline at end of file
@@ -23,4 +23,69 @@ fn test_time() -> Result<(), Box<dyn Error>> {
     let deserialized = tl_proto::deserialize::<Message>(raw.as_slice())?;
     assert_eq!(deserialized, message);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_block_id_from_str() -> Result<(), Box<dyn Error>> {
+    use common::{BlockId, BlockIdExt, ParseIdError, ZeroStateIdExt};
+
+    let root_hash = Int256::from_hex("e1bf2f5a3b6fb7a9c4a8a3f4a1d3d40b2b8b1a6ed2ec0e2e5b4f6a0a1c2d3e4f")?;
+    let file_hash = Int256([0xfb; 32]);
+    let block_id = BlockIdExt { workchain: -1, shard: 0x8000000000000000, seqno: 34835953, root_hash, file_hash };
+    let formatted = block_id.to_string();
+    assert_eq!(formatted.parse::<BlockIdExt>()?, block_id);
+    let base64 = format!("(-1,8000000000000000,34835953):{}:{}", base64::encode(block_id.root_hash.0), base64::encode(block_id.file_hash.0));
+    assert_eq!(base64.parse::<BlockIdExt>()?, block_id);
+    let url_safe = format!("(-1,8000000000000000,34835953):{}:{}", block_id.root_hash, base64::encode_config(block_id.file_hash.0, base64::URL_SAFE));
+    assert_eq!(url_safe.parse::<BlockIdExt>()?, block_id);
+
+    let short = BlockId { workchain: 0, shard: 0xa000000000000000, seqno: 1 };
+    assert_eq!(short.to_string().parse::<BlockId>()?, short);
+    assert_eq!("(0,a000000000000000,1)".parse::<BlockId>()?, short);
+
+    let zero_state = ZeroStateIdExt { workchain: -1, root_hash: block_id.root_hash.clone(), file_hash: block_id.file_hash.clone() };
+    assert_eq!(zero_state.to_string().parse::<ZeroStateIdExt>()?, zero_state);
+
+    assert!(matches!(formatted.replace('(', "").parse::<BlockIdExt>(), Err(ParseIdError::InvalidFormat(_))));
+    assert!(matches!(format!("{}:00", formatted).parse::<BlockIdExt>(), Err(ParseIdError::InvalidFormat(_))));
+    assert_eq!(formatted.replace("34835953", "x").parse::<BlockIdExt>(), Err(ParseIdError::InvalidField("seqno")));
+    assert_eq!(formatted[..formatted.len() - 2].parse::<BlockIdExt>(), Err(ParseIdError::InvalidField("file_hash")));
+    assert_eq!("(0,g,1)".parse::<BlockId>(), Err(ParseIdError::InvalidField("shard")));
+    assert!(matches!("(0,8000000000000000)".parse::<BlockId>(), Err(ParseIdError::InvalidFormat(_))));
+    Ok(())
+}
+
+#[test]
+fn test_raw_query() -> Result<(), Box<dyn Error>> {
+    // same liteServer.getTime query as in test_time, with the request given pre-serialized
+    let raw = hex::decode("7af98bb435263e6c95d6fecb497dfd0aa5f031e7d412986b5ce720496db512052e8f2d100cdf068c7904345aad16000000000000")?;
+    let message = Message::Query {
+        query_id: Int256::from_hex("35263e6c95d6fecb497dfd0aa5f031e7d412986b5ce720496db512052e8f2d10")?,
+        query: request::LiteQuery {
+            wrapped_request: WrappedRequest {
+                request: Request::Raw(hex::decode("345aad16")?),
+                wait_masterchain_seqno: None,
+            }
+        }
+    };
+    assert_eq!(tl_proto::serialize(message), raw);
+
+    // answers are read undecoded, even with unknown constructor
+    let answer = hex::decode("1684ac0f35263e6c95d6fecb497dfd0aa5f031e7d412986b5ce720496db512052e8f2d1008efbeadde01020304000000")?;
+    let deserialized = tl_proto::deserialize::<Message>(&answer)?;
+    assert!(matches!(&deserialized, Message::Answer { answer: response::Response::Raw(data), .. } if data == &hex::decode("efbeadde01020304")?));
+    assert_eq!(tl_proto::serialize(deserialized), answer);
+    Ok(())
+}
+
+#[test]
+fn test_run_smc_method_mode() -> Result<(), Box<dyn Error>> {
+    let id = "(-1,8000000000000000,1):0000000000000000000000000000000000000000000000000000000000000000:0000000000000000000000000000000000000000000000000000000000000000".parse()?;
+    let account = common::AccountId { workchain: 0, id: Int256([1; 32]) };
+    let request = request::RunSmcMethod::new(id, account, 85143, vec![]).with_proofs().with_result().with_lib_extras();
+    let serialized = tl_proto::serialize(Request::RunSmcMethod(request.clone()));
+    // mode follows the constructor id
+    assert_eq!(serialized[4..8], 0b10101u32.to_le_bytes());
+    assert_eq!(tl_proto::deserialize::<Request>(&serialized)?, Request::RunSmcMethod(request));
+    Ok(())
+}
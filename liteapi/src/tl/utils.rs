@@ -51,10 +51,42 @@ pub mod struct_as_bytes {
     }
 }
 
+/// Answer of `adnl.message.answer`, read undecoded as [`Response::Raw`] so that answers to
+/// unknown requests do not break the connection
+pub mod answer_as_bytes {
+    use tl_proto::{TlPacket, TlRead, TlResult, TlWrite};
+
+    use super::Response;
+
+    pub fn size_hint(v: &Response) -> usize {
+        match v {
+            Response::Raw(data) => data.as_slice().max_size_hint(),
+            v => super::struct_as_bytes::size_hint(v),
+        }
+    }
+
+    pub fn write<P: TlPacket>(v: &Response, packet: &mut P) {
+        match v {
+            Response::Raw(data) => data.as_slice().write_to(packet),
+            v => super::struct_as_bytes::write(v, packet),
+        }
+    }
+
+    pub fn read(packet: &[u8], offset: &mut usize) -> TlResult<Response> {
+        Ok(Response::Raw(<&[u8]>::read_from(packet, offset)?.to_vec()))
+    }
+}
+
 pub trait FromResponse: Sized {
     fn from_response(response: Response) -> Result<Self, LiteError>;
 }
 
+impl FromResponse for Response {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        Ok(response)
+    }
+}
+
 impl FromResponse for MasterchainInfo {
     fn from_response(response: Response) -> Result<Self, LiteError> {
         match response {
@@ -235,6 +267,78 @@ impl FromResponse for LibraryResult {
     }
 }
 
+impl FromResponse for BlockTransactionsExt {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        match response {
+            Response::BlockTransactionsExt(s) => Ok(s),
+            _ => Err(LiteError::UnexpectedMessage)
+        }
+    }
+}
+
+impl FromResponse for LibraryResultWithProof {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        match response {
+            Response::LibraryResultWithProof(s) => Ok(s),
+            _ => Err(LiteError::UnexpectedMessage)
+        }
+    }
+}
+
+impl FromResponse for ShardBlockProof {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        match response {
+            Response::ShardBlockProof(s) => Ok(s),
+            _ => Err(LiteError::UnexpectedMessage)
+        }
+    }
+}
+
+impl FromResponse for LookupBlockResult {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        match response {
+            Response::LookupBlockResult(s) => Ok(s),
+            _ => Err(LiteError::UnexpectedMessage)
+        }
+    }
+}
+
+impl FromResponse for OutMsgQueueSizes {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        match response {
+            Response::OutMsgQueueSizes(s) => Ok(s),
+            _ => Err(LiteError::UnexpectedMessage)
+        }
+    }
+}
+
+impl FromResponse for BlockOutMsgQueueSize {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        match response {
+            Response::BlockOutMsgQueueSize(s) => Ok(s),
+            _ => Err(LiteError::UnexpectedMessage)
+        }
+    }
+}
+
+impl FromResponse for DispatchQueueInfo {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        match response {
+            Response::DispatchQueueInfo(s) => Ok(s),
+            _ => Err(LiteError::UnexpectedMessage)
+        }
+    }
+}
+
+impl FromResponse for DispatchQueueMessages {
+    fn from_response(response: Response) -> Result<Self, LiteError> {
+        match response {
+            Response::DispatchQueueMessages(s) => Ok(s),
+            _ => Err(LiteError::UnexpectedMessage)
+        }
+    }
+}
+
 impl FromResponse for Error {
     fn from_response(response: Response) -> Result<Self, LiteError> {
         match response {
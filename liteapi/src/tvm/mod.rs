@@ -0,0 +1,20 @@
+//! TVM values passed to and returned from get-methods
+mod stack;
+#[cfg(test)]
+mod tests;
+
+use crc::{Crc, CRC_16_XMODEM};
+
+use crate::boc::BocError;
+
+pub use num_bigint::BigInt;
+pub use stack::{deserialize_stack, serialize_stack, GetMethodResult, TvmStackEntry};
+
+type Result<T> = std::result::Result<T, BocError>;
+
+const CRC16: Crc<u16> = Crc::<u16>::new(&CRC_16_XMODEM);
+
+/// Id of get-method as computed by FunC, `(crc16(name) & 0xffff) | 0x10000`
+pub fn method_id(name: &str) -> u64 {
+    CRC16.checksum(name.as_bytes()) as u64 | 0x10000
+}
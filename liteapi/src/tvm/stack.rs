@@ -0,0 +1,259 @@
+use num_bigint::{BigInt, Sign};
+
+use crate::boc::{BocError, Cell, CellBuilder, CellSlice};
+use crate::tl::response::RunMethodResult;
+
+use super::Result;
+
+/// Value of TVM stack, `VmStackValue`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TvmStackEntry {
+    Null,
+    /// Signed 257-bit integer
+    Int(BigInt),
+    NaN,
+    Cell(Cell),
+    /// Bits and references of the slice stored as a separate cell
+    Slice(Cell),
+    Builder(Cell),
+    Tuple(Vec<TvmStackEntry>),
+    /// Continuation, `VmCont` is not decoded and kept without the tag of stack value
+    Cont(Cell),
+}
+
+impl TvmStackEntry {
+    pub fn parse(slice: &mut CellSlice) -> Result<Self> {
+        Ok(match slice.load_uint(8)? {
+            0x00 => Self::Null,
+            0x01 => Self::Int(BigInt::from(slice.load_int(64)?)),
+            // vm_stk_int#0201_ value:int257 and vm_stk_nan#02ff
+            0x02 => match slice.load_uint(7)? {
+                0x00 => Self::Int(load_int257(slice)?),
+                0x7f if slice.load_bit()? => Self::NaN,
+                _ => return Err(BocError::InvalidCell("invalid stack value tag")),
+            },
+            0x03 => Self::Cell(slice.load_reference()?),
+            0x04 => Self::Slice(load_vm_cell_slice(slice)?),
+            0x05 => Self::Builder(slice.load_reference()?),
+            0x06 => {
+                let cont = CellBuilder::new().store_slice(slice)?.build()?;
+                slice.skip_bits(slice.remaining_bits())?;
+                while slice.remaining_refs() > 0 {
+                    slice.load_reference()?;
+                }
+                Self::Cont(cont)
+            }
+            0x07 => {
+                let len = slice.load_uint(16)? as usize;
+                Self::Tuple(load_tuple(slice, len)?)
+            }
+            _ => return Err(BocError::InvalidCell("invalid stack value tag")),
+        })
+    }
+
+    pub fn store(&self, builder: &mut CellBuilder) -> Result<()> {
+        match self {
+            Self::Null => {
+                builder.store_uint(0x00, 8)?;
+            }
+            Self::Int(value) => match i64::try_from(value) {
+                Ok(value) => {
+                    builder.store_uint(0x01, 8)?.store_int(value, 64)?;
+                }
+                Err(_) => {
+                    builder.store_uint(0x0100, 15)?;
+                    store_int257(builder, value)?;
+                }
+            },
+            Self::NaN => {
+                builder.store_uint(0x02ff, 16)?;
+            }
+            Self::Cell(cell) => {
+                builder.store_uint(0x03, 8)?.store_reference(cell.clone())?;
+            }
+            Self::Slice(cell) => {
+                // _ cell:^Cell st_bits:(## 10) end_bits:(## 10) st_ref:(#<= 4) end_ref:(#<= 4)
+                builder.store_uint(0x04, 8)?.store_reference(cell.clone())?;
+                builder.store_uint(0, 10)?.store_uint(cell.bit_len() as u64, 10)?;
+                builder.store_uint(0, 3)?.store_uint(cell.references().len() as u64, 3)?;
+            }
+            Self::Builder(cell) => {
+                builder.store_uint(0x05, 8)?.store_reference(cell.clone())?;
+            }
+            Self::Cont(cont) => {
+                builder.store_uint(0x06, 8)?.store_slice(&cont.parse()?)?;
+            }
+            Self::Tuple(items) => {
+                if items.len() > u16::MAX as usize {
+                    return Err(BocError::IntegerOverflow);
+                }
+                builder.store_uint(0x07, 8)?.store_uint(items.len() as u64, 16)?;
+                store_tuple(builder, items)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Value of `Int` that fits into `i64`
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int(value) => i64::try_from(value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Cell of `Cell`, `Slice` or `Builder`
+    pub fn as_cell(&self) -> Option<&Cell> {
+        match self {
+            Self::Cell(cell) | Self::Slice(cell) | Self::Builder(cell) => Some(cell),
+            _ => None,
+        }
+    }
+}
+
+impl From<i64> for TvmStackEntry {
+    fn from(value: i64) -> Self {
+        Self::Int(BigInt::from(value))
+    }
+}
+
+impl From<BigInt> for TvmStackEntry {
+    fn from(value: BigInt) -> Self {
+        Self::Int(value)
+    }
+}
+
+/// Serializes stack to `VmStack`, the last entry is the top of the stack
+pub fn serialize_stack(entries: &[TvmStackEntry]) -> Result<Cell> {
+    let mut builder = CellBuilder::new();
+    builder.store_uint(entries.len() as u64, 24)?;
+    if let Some((top, rest)) = entries.split_last() {
+        // vm_stk_cons#_ rest:^(VmStackList n) tos:VmStackValue
+        let mut list = CellBuilder::new().build()?;
+        for entry in rest {
+            let mut cons = CellBuilder::new();
+            cons.store_reference(list)?;
+            entry.store(&mut cons)?;
+            list = cons.build()?;
+        }
+        builder.store_reference(list)?;
+        top.store(&mut builder)?;
+    }
+    builder.build()
+}
+
+/// Decodes `VmStack`, the last entry is the top of the stack
+pub fn deserialize_stack(cell: &Cell) -> Result<Vec<TvmStackEntry>> {
+    let mut slice = cell.parse()?;
+    let depth = slice.load_uint(24)? as usize;
+    let mut entries = Vec::with_capacity(depth);
+    for _ in 0..depth {
+        let rest = slice.load_reference()?;
+        entries.push(TvmStackEntry::parse(&mut slice)?);
+        slice = rest.parse()?;
+    }
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Exit code and result stack of get-method
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetMethodResult {
+    pub exit_code: i32,
+    /// Result stack, the last entry is the top of the stack
+    pub stack: Vec<TvmStackEntry>,
+}
+
+impl GetMethodResult {
+    /// Decodes result of `liteServer.runSmcMethod` queried with the result mode flag
+    pub fn from_response(response: &RunMethodResult) -> Result<Self> {
+        let result = response.result.as_ref().ok_or(BocError::InvalidHeader("result stack is not present"))?;
+        Ok(Self { exit_code: response.exit_code, stack: deserialize_stack(&Cell::from_boc(result)?)? })
+    }
+
+    /// Whether the get-method finished with exit code 0 or 1
+    pub fn is_success(&self) -> bool {
+        self.exit_code == 0 || self.exit_code == 1
+    }
+}
+
+fn load_int257(slice: &mut CellSlice) -> Result<BigInt> {
+    let negative = slice.load_bit()?;
+    let value = BigInt::from_bytes_be(Sign::Plus, &slice.load_bytes(32)?);
+    Ok(if negative { value - (BigInt::from(1) << 256) } else { value })
+}
+
+fn store_int257(builder: &mut CellBuilder, value: &BigInt) -> Result<()> {
+    let bound = BigInt::from(1) << 256;
+    if *value >= bound || *value < -&bound {
+        return Err(BocError::IntegerOverflow);
+    }
+    let unsigned = if value.sign() == Sign::Minus { value + &bound } else { value.clone() };
+    let (_, bytes) = unsigned.to_bytes_be();
+    let mut padded = vec![0; 32 - bytes.len()];
+    padded.extend_from_slice(&bytes);
+    builder.store_bit(value.sign() == Sign::Minus)?.store_bytes(&padded)?;
+    Ok(())
+}
+
+/// Loads `VmCellSlice` into a cell with the bits and references of the slice
+fn load_vm_cell_slice(slice: &mut CellSlice) -> Result<Cell> {
+    let cell = slice.load_reference()?;
+    let st_bits = slice.load_uint(10)? as usize;
+    let end_bits = slice.load_uint(10)? as usize;
+    let st_ref = slice.load_uint(3)? as usize;
+    let end_ref = slice.load_uint(3)? as usize;
+    if st_bits > end_bits || end_bits > cell.bit_len() || st_ref > end_ref || end_ref > cell.references().len() {
+        return Err(BocError::InvalidCell("invalid slice bounds"));
+    }
+    if st_bits == 0 && end_bits == cell.bit_len() && st_ref == 0 && end_ref == cell.references().len() {
+        return Ok(cell);
+    }
+    let mut window = cell.parse()?;
+    window.skip_bits(st_bits)?;
+    let mut builder = CellBuilder::new();
+    builder.store_bits(&window.load_bits(end_bits - st_bits)?, end_bits - st_bits)?;
+    for reference in &cell.references()[st_ref..end_ref] {
+        builder.store_reference(reference.clone())?;
+    }
+    builder.build()
+}
+
+/// Loads `VmTuple len`, its last item is in a reference and the others are in `VmTupleRef`
+fn load_tuple(slice: &mut CellSlice, len: usize) -> Result<Vec<TvmStackEntry>> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    let mut items = match len - 1 {
+        0 => Vec::new(),
+        1 => vec![TvmStackEntry::parse(&mut slice.load_reference()?.parse()?)?],
+        head => load_tuple(&mut slice.load_reference()?.parse()?, head)?,
+    };
+    items.push(TvmStackEntry::parse(&mut slice.load_reference()?.parse()?)?);
+    Ok(items)
+}
+
+fn store_tuple(builder: &mut CellBuilder, items: &[TvmStackEntry]) -> Result<()> {
+    let Some((last, head)) = items.split_last() else {
+        return Ok(());
+    };
+    match head {
+        [] => {}
+        [item] => {
+            builder.store_reference(entry_cell(item)?)?;
+        }
+        _ => {
+            let mut tuple = CellBuilder::new();
+            store_tuple(&mut tuple, head)?;
+            builder.store_reference(tuple.build()?)?;
+        }
+    }
+    builder.store_reference(entry_cell(last)?)?;
+    Ok(())
+}
+
+fn entry_cell(entry: &TvmStackEntry) -> Result<Cell> {
+    let mut builder = CellBuilder::new();
+    entry.store(&mut builder)?;
+    builder.build()
+}
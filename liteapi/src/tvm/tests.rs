@@ -0,0 +1,73 @@
+use std::error::Error;
+
+use crate::boc::{Cell, CellBuilder};
+
+use super::*;
+
+type TestResult = std::result::Result<(), Box<dyn Error>>;
+
+#[test]
+fn test_stack_round_trip() -> TestResult {
+    let cell = CellBuilder::new().store_bytes(b"cell")?.store_reference(CellBuilder::new().build()?)?.build()?;
+    let cont = CellBuilder::new().store_uint(0, 2)?.store_reference(cell.clone())?.build()?;
+    let big = BigInt::from(1) << 255u32;
+    let entries = vec![
+        TvmStackEntry::Null,
+        TvmStackEntry::from(-5),
+        TvmStackEntry::Int(big),
+        TvmStackEntry::Int(-(BigInt::from(1) << 256u32)),
+        TvmStackEntry::NaN,
+        TvmStackEntry::Cell(cell.clone()),
+        TvmStackEntry::Slice(cell.clone()),
+        TvmStackEntry::Builder(cell.clone()),
+        TvmStackEntry::Tuple(Vec::new()),
+        TvmStackEntry::Tuple(vec![TvmStackEntry::from(1)]),
+        TvmStackEntry::Tuple((0..5).map(TvmStackEntry::from).chain([TvmStackEntry::Tuple(vec![TvmStackEntry::Null; 2])]).collect()),
+        TvmStackEntry::Cont(cont),
+    ];
+    let stack = serialize_stack(&entries)?;
+    assert_eq!(deserialize_stack(&Cell::from_boc(&stack.to_boc())?)?, entries);
+    assert_eq!(entries[1].as_i64(), Some(-5));
+    assert_eq!(entries[2].as_i64(), None);
+    assert_eq!(entries[6].as_cell(), Some(&cell));
+
+    assert!(serialize_stack(&[TvmStackEntry::Int(BigInt::from(1) << 256u32)]).is_err());
+    assert!(deserialize_stack(&serialize_stack(&[])?)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_stack_layout() -> TestResult {
+    // small integers are stored as vm_stk_tinyint, the top of the stack is in the root cell
+    let stack = serialize_stack(&[TvmStackEntry::Null, TvmStackEntry::from(7)])?;
+    let mut slice = stack.parse()?;
+    assert_eq!((slice.load_uint(24)?, slice.remaining_bits()), (2, 8 + 64));
+    assert_eq!((slice.load_uint(8)?, slice.load_int(64)?), (0x01, 7));
+    let mut rest = slice.load_reference()?.parse()?;
+    assert_eq!((rest.load_uint(8)?, rest.remaining_bits()), (0x00, 0));
+    assert!(rest.load_reference()?.parse()?.is_empty());
+
+    // vm_stk_int#0201_ value:int257
+    let stack = serialize_stack(&[TvmStackEntry::Int(-(BigInt::from(1) << 100u32))])?;
+    let mut slice = stack.parse()?;
+    slice.skip_bits(24)?;
+    assert_eq!((slice.load_uint(15)?, slice.load_bit()?, slice.remaining_bits()), (0x0100, true, 256));
+
+    // slice with st_bits 4, end_bits 12, st_ref 1 and end_ref 2
+    let inner = CellBuilder::new().build()?;
+    let cell = CellBuilder::new().store_uint(0xabcd, 16)?.store_reference(inner.clone())?.store_reference(inner.clone())?.build()?;
+    let mut builder = CellBuilder::new();
+    builder.store_uint(1, 24)?.store_reference(CellBuilder::new().build()?)?;
+    builder.store_uint(0x04, 8)?.store_reference(cell)?.store_uint(4, 10)?.store_uint(12, 10)?.store_uint(1, 3)?.store_uint(2, 3)?;
+    let expected = CellBuilder::new().store_uint(0xbc, 8)?.store_reference(inner)?.build()?;
+    assert_eq!(deserialize_stack(&builder.build()?)?, [TvmStackEntry::Slice(expected)]);
+    Ok(())
+}
+
+#[test]
+fn test_method_id() {
+    assert_eq!(method_id("seqno"), 85143);
+    assert_eq!(method_id("get_public_key"), 78748);
+    assert_eq!(method_id("get_wallet_data"), 97026);
+    assert_eq!(method_id("get_jetton_data"), 106029);
+}
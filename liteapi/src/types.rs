@@ -5,6 +5,11 @@ use tower::Service;
 
 use crate::tl::{request::WrappedRequest, response::Response};
 
+/// Liteserver error code for queries about blocks and states it does not have yet
+pub const ERROR_CODE_NOT_READY: i32 = 651;
+/// Liteserver error code for queries it failed to answer in time
+pub const ERROR_CODE_TIMEOUT: i32 = 652;
+
 #[derive(Debug, Error)]
 pub enum LiteError {
     #[error("Liteserver error")]
@@ -15,10 +20,51 @@ pub enum LiteError {
     UnexpectedMessage,
     #[error("ADNL error")]
     AdnlError(#[from] AdnlError),
+    #[error("Liteserver did not answer in time")]
+    Timeout,
+    #[error("No liteservers available")]
+    NoAvailableServers,
+    #[error("Liteserver returned invalid proof")]
+    InvalidProof(#[from] crate::proof::ProofError),
+    #[error("Invalid bag of cells")]
+    BocError(#[from] crate::boc::BocError),
     #[error("Unknown error")]
     UnknownError(#[from] Box<dyn std::error::Error + Send + Sync + 'static>)
 }
 
+impl LiteError {
+    /// Whether the error is caused by the connection to liteserver rather than by the query itself
+    pub fn is_transport(&self) -> bool {
+        matches!(self, LiteError::AdnlError(_) | LiteError::Timeout)
+    }
+
+    /// Whether the query may succeed if repeated later, e.g. after reconnection or once liteserver
+    /// catches up with the network
+    pub fn is_transient(&self) -> bool {
+        match self {
+            LiteError::ServerError(error) => {
+                error.code == ERROR_CODE_NOT_READY
+                    || error.code == ERROR_CODE_TIMEOUT
+                    || std::string::String::from_utf8_lossy(error.message.as_bytes()).contains("not in db")
+            }
+            error => error.is_transport(),
+        }
+    }
+
+    /// Recovers the original error after it was boxed by [`tower::buffer::Buffer`]
+    pub(crate) fn from_boxed(error: Box<dyn std::error::Error + Send + Sync + 'static>) -> Self {
+        // buffer worker stops only when the underlying connection is broken
+        if error.is::<tower::buffer::error::ServiceError>() || error.is::<tower::buffer::error::Closed>() {
+            log::debug!("Buffered service failed: {:?}", error);
+            return LiteError::AdnlError(AdnlError::EndOfStream);
+        }
+        match error.downcast::<LiteError>() {
+            Ok(error) => *error,
+            Err(error) => LiteError::UnknownError(error),
+        }
+    }
+}
+
 pub trait LiteService: Service<WrappedRequest, Response = Response, Error = LiteError> where Self::Future: Send + 'static {}
 
 impl<T> LiteService for T where T: Service<WrappedRequest, Response = Response, Error = LiteError>, T::Future: Send + 'static {}
\ No newline at end of file
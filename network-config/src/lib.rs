@@ -26,9 +26,34 @@ pub struct ConfigLiteServer {
     pub id: ConfigPublicKey,
 }
 
+#[serde_with::serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigBlockId {
+    pub workchain: i32,
+    pub shard: i64,
+    pub seqno: u32,
+    #[serde_as(as = "serde_with::base64::Base64")]
+    pub root_hash: [u8; 32],
+    #[serde_as(as = "serde_with::base64::Base64")]
+    pub file_hash: [u8; 32],
+}
+
+/// Blocks trusted by the network config, from which other blocks are proven
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigValidator {
+    pub zero_state: ConfigBlockId,
+    /// Newer trusted key block, if present proofs start from it instead of the zerostate
+    #[serde(default)]
+    pub init_block: Option<ConfigBlockId>,
+    #[serde(default)]
+    pub hardforks: Vec<ConfigBlockId>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ConfigGlobal {
     pub liteservers: Vec<ConfigLiteServer>,
+    #[serde(default)]
+    pub validator: Option<ConfigValidator>,
 }
 
 impl FromStr for ConfigGlobal {